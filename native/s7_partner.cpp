@@ -199,6 +199,7 @@ TConnectionServer::TConnectionServer()
     cs = new TSnapCriticalSection;
     memset(Partners,0,sizeof(Partners));
     FRunning = false;
+    Destroying = false;
     PartnersCount = 0;
 }
 //------------------------------------------------------------------------------
@@ -0,0 +1,149 @@
+//
+// builder.rs
+// Copyright (C) 2021 gmg137 <gmg137 AT live.com>
+// snap7-rs is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//          http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+// EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+// MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+//
+use crate::{client::S7Client, model::*};
+use anyhow::*;
+
+/// S7 客户端构建器
+///
+/// 未显式指定连接类型时，按 CPU 系列选择最通用的连接类型(见 CpuFamily::default_connection_type)，
+/// 未指定 CPU 系列时使用 snap7 默认的 PG。
+///
+/// # Examples
+/// ```ignore
+/// use rust_snap7::{ClientBuilder, CpuFamily};
+///
+/// let client = ClientBuilder::new("192.168.1.123")
+///     .cpu_family(CpuFamily::S71200)
+///     .slot(1)
+///     .connect()
+///     .unwrap();
+/// assert_eq!(client.connection_type(), rust_snap7::ConnType::OP);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    address: String,
+    rack: i32,
+    slot: i32,
+    port: Option<u16>,
+    cpu_family: Option<CpuFamily>,
+    connection_type: Option<ConnType>,
+}
+
+impl ClientBuilder {
+    ///
+    /// 以 PLC 地址创建构建器，默认机架号 0、插槽号 1。
+    ///
+    pub fn new(address: &str) -> Self {
+        ClientBuilder {
+            address: address.to_string(),
+            rack: 0,
+            slot: 1,
+            port: None,
+            cpu_family: None,
+            connection_type: None,
+        }
+    }
+
+    /// 设置机架号
+    pub fn rack(mut self, rack: i32) -> Self {
+        self.rack = rack;
+        self
+    }
+
+    /// 设置插槽号
+    pub fn slot(mut self, slot: i32) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    /// 设置远程端口，默认为 102
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// 设置(假定的) CPU 系列，用于选择默认连接类型
+    pub fn cpu_family(mut self, cpu_family: CpuFamily) -> Self {
+        self.cpu_family = Some(cpu_family);
+        self
+    }
+
+    /// 显式设置连接类型，优先于 CPU 系列的默认值
+    pub fn connection_type(mut self, connection_type: ConnType) -> Self {
+        self.connection_type = Some(connection_type);
+        self
+    }
+
+    ///
+    /// 最终使用的连接类型。
+    ///
+    pub fn resolved_connection_type(&self) -> ConnType {
+        self.connection_type
+            .or_else(|| {
+                self.cpu_family
+                    .map(|family| family.default_connection_type())
+            })
+            .unwrap_or(ConnType::PG)
+    }
+
+    ///
+    /// 创建客户端并连接到 PLC。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 已连接的客户端
+    ///  - Err: 设置参数或连接失败
+    ///
+    pub fn connect(&self) -> Result<S7Client> {
+        let client = S7Client::create();
+        if let Some(port) = self.port {
+            client.set_param(InternalParam::RemotePort, InternalParamValue::U16(port))?;
+        }
+        client.set_connection_type(self.resolved_connection_type())?;
+        client.connect_to(&self.address, self.rack, self.slot)?;
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+
+    #[test]
+    fn test_resolved_connection_type() {
+        let builder = ClientBuilder::new("127.0.0.1");
+        assert_eq!(builder.resolved_connection_type(), ConnType::PG);
+        let builder = builder.cpu_family(CpuFamily::S71500);
+        assert_eq!(builder.resolved_connection_type(), ConnType::OP);
+        let builder = builder.connection_type(ConnType::S7Basic(0x03));
+        assert_eq!(builder.resolved_connection_type(), ConnType::S7Basic(0x03));
+    }
+
+    #[test]
+    fn test_connect_with_each_connection_type() {
+        let (server, port) = start_server(&mut []);
+
+        for conn_type in [ConnType::PG, ConnType::OP, ConnType::S7Basic(0x03)] {
+            let client = ClientBuilder::new("127.0.0.1")
+                .port(port)
+                .connection_type(conn_type)
+                .connect()
+                .unwrap();
+            assert_eq!(client.connection_type(), conn_type);
+            client.disconnect().unwrap();
+        }
+
+        server.stop().unwrap();
+    }
+}
@@ -10,18 +10,46 @@
 // MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
 // See the Mulan PSL v2 for more details.
 //
-use crate::{ffi::*, model::*};
+use crate::{
+    error::*,
+    ffi::*,
+    model::*,
+    utils::{
+        address::TagAddress,
+        getters,
+        hexdump::hexdump_at,
+        layout::{Layout, Value},
+        setters,
+    },
+};
 use anyhow::*;
 use std::{
+    collections::HashMap,
     ffi::{CStr, CString},
+    io::Read,
+    net::Ipv4Addr,
     os::raw::*,
+    sync::{
+        atomic::{AtomicI32, AtomicU16, AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::Duration,
 };
 
+/// snap7 客户端区块缓冲区的大小，下载的区块不能超过该长度
+const MAX_BLOCK_SIZE: i32 = 65536;
+
+/// scan_subnet() 同时探测的最大主机数
+const SCAN_CONCURRENCY: usize = 32;
+
+/// scan_subnet() 接受的最短子网前缀长度，/16 最多 65534 个主机
+const SCAN_MIN_PREFIX: u32 = 16;
+
 /// S7 客户端
 ///
 /// # Examples
 /// ```ignore
-/// use snap7_rs::S7Client;
+/// use rust_snap7::S7Client;
 /// use std::ffi::*;
 /// use std::os::raw::*;
 ///
@@ -41,8 +69,53 @@ use std::{
 ///     println!("连接 PLC 失败！");
 /// }
 /// ```
+///
+/// # 释放顺序
+///
+/// Drop 时先调用 `Cli_Destroy` 断开连接并结束 snap7 内部线程，之后才释放 set_as_callback()
+/// 设置的回调，因此回调不会在释放后被调用。需要在离开作用域前确定地断开并释放资源时，使用 close()。
 pub struct S7Client {
     handle: usize,
+    as_callback: Mutex<Option<CallbackBox>>,
+    conn_type: AtomicU16,
+    /// db_write_checked() 使用的 DB 大小缓存，断开连接时清空
+    db_sizes: Mutex<HashMap<i32, usize>>,
+    /// 最近一次发起的异步任务的操作码
+    async_op: AtomicI32,
+}
+
+/// 已交给 snap7 的回调闭包，记录释放函数以便回收。
+struct CallbackBox {
+    ptr: usize,
+    drop_fn: unsafe fn(usize),
+}
+
+impl CallbackBox {
+    fn new<F>(callback: F) -> Self {
+        unsafe fn drop_box<F>(ptr: usize) {
+            drop(Box::from_raw(ptr as *mut F));
+        }
+        CallbackBox {
+            ptr: Box::into_raw(Box::new(callback)) as usize,
+            drop_fn: drop_box::<F>,
+        }
+    }
+}
+
+impl Drop for CallbackBox {
+    fn drop(&mut self) {
+        unsafe { (self.drop_fn)(self.ptr) }
+    }
+}
+
+impl ParamAccess for S7Client {
+    fn get_param(&self, param: InternalParam, value: &mut InternalParamValue) -> Result<()> {
+        S7Client::get_param(self, param, value)
+    }
+
+    fn set_param(&self, param: InternalParam, value: InternalParamValue) -> Result<()> {
+        S7Client::set_param(self, param, value)
+    }
 }
 
 impl Drop for S7Client {
@@ -50,6 +123,7 @@ impl Drop for S7Client {
         unsafe {
             Cli_Destroy(&mut self.handle as *mut S7Object);
         }
+        self.replace_as_callback(None);
     }
 }
 
@@ -64,6 +138,10 @@ impl S7Client {
     pub fn create() -> S7Client {
         S7Client {
             handle: unsafe { Cli_Create() },
+            as_callback: Mutex::new(None),
+            conn_type: AtomicU16::new(CONNTYPE_PG),
+            db_sizes: Mutex::new(HashMap::new()),
+            async_op: AtomicI32::new(0),
         }
     }
 
@@ -80,20 +158,24 @@ impl S7Client {
     ///  - Err: 设置失败
     ///
     pub fn set_connection_type(&self, value: ConnType) -> Result<()> {
-        let value = match value {
-            ConnType::PG => 0x01,
-            ConnType::OP => 0x02,
-            ConnType::S7Basic(v) => v,
-        };
+        let value = value.resource();
         unsafe {
             let res = Cli_SetConnectionType(self.handle, value);
             if res == 0 {
+                self.conn_type.store(value, Ordering::Relaxed);
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::client(res))
         };
     }
 
+    ///
+    /// 获取当前设置的连接类型，未设置时为 snap7 默认的 PG。
+    ///
+    pub fn connection_type(&self) -> ConnType {
+        ConnType::from_resource(self.conn_type.load(Ordering::Relaxed))
+    }
+
     ///
     /// 通过指定 IP 和机架号、插槽号连接到 PLC。
     ///
@@ -126,7 +208,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -158,7 +240,43 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 创建客户端并通过本地/远程 TSAP 连接到 PLC，相当于依次调用 create()、
+    /// set_connection_params() 和 connect()。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - address: PLC 地址
+    ///  - local_tsap: 本地 TSAP
+    ///  - remote_tsap: 远程 TSAP
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 已连接的客户端
+    ///  - Err: 设置参数或连接失败
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_snap7::{S7Client, Tsap};
+    ///
+    /// let client = S7Client::connect_tsap(
+    ///     "192.168.1.123",
+    ///     Tsap::new(0x01, 0, 0),
+    ///     Tsap::new(0x03, 0, 2),
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn connect_tsap(address: &str, local_tsap: Tsap, remote_tsap: Tsap) -> Result<S7Client> {
+        Self::create().connect_tsap_with(address, local_tsap, remote_tsap)
+    }
+
+    fn connect_tsap_with(self, address: &str, local_tsap: Tsap, remote_tsap: Tsap) -> Result<Self> {
+        self.set_connection_params(address, local_tsap.value(), remote_tsap.value())?;
+        self.connect()?;
+        Ok(self)
     }
 
     ///
@@ -176,7 +294,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -185,16 +303,20 @@ impl S7Client {
     /// **返回值:**
     ///
     ///  - Ok: 操作成功
-    ///  - Err: 操作失败
+    ///  - Err: 等待正在进行的异步任务结束失败，或断开操作失败
     ///
-    /// `注: 如果客户端参数是一个有效的句柄，这个函数总是返回 true，它可以被安全地多次调用。这个函数在 S7Client drop 时也会被调用。`
+    /// `注: 断开前会先调用 abort_async() 等待正在进行的异步任务结束，等待失败时返回 Err 且不会断开连接。`
+    ///
+    /// `注: 这个函数可以被安全地多次调用，S7Client drop 时 snap7 也会自动断开连接。`
     ///
     pub fn disconnect(&self) -> Result<()> {
+        self.abort_async()?;
+        self.db_sizes.lock().unwrap().clear();
         let res = unsafe { Cli_Disconnect(self.handle) };
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -223,7 +345,7 @@ impl S7Client {
                     *value = InternalParamValue::U32(u32::from_le_bytes(buff));
                     Ok(())
                 } else {
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::client(res))
                 }
             },
             InternalParam::LocalPort
@@ -241,7 +363,7 @@ impl S7Client {
                     *value = InternalParamValue::U16(u16::from_le_bytes(buff));
                     Ok(())
                 } else {
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::client(res))
                 }
             },
             _ => unsafe {
@@ -255,7 +377,7 @@ impl S7Client {
                     *value = InternalParamValue::I32(i32::from_le_bytes(buff));
                     Ok(())
                 } else {
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::client(res))
                 }
             },
         }
@@ -287,7 +409,7 @@ impl S7Client {
                     if res == 0 {
                         return Ok(());
                     }
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::client(res))
                 } else {
                     bail!("{}", Self::error_text(-1))
                 }
@@ -307,7 +429,7 @@ impl S7Client {
                     if res == 0 {
                         return Ok(());
                     }
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::client(res))
                 } else {
                     bail!("{}", Self::error_text(-1))
                 }
@@ -323,7 +445,7 @@ impl S7Client {
                     if res == 0 {
                         return Ok(());
                     }
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::client(res))
                 } else {
                     bail!("{}", Self::error_text(-1))
                 }
@@ -331,6 +453,134 @@ impl S7Client {
         }
     }
 
+    ///
+    /// 读取客户端支持的所有内部参数，便于诊断输出当前配置。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: ClientParams 结构体
+    ///  - Err: 读取某个参数失败
+    ///
+    pub fn params(&self) -> Result<ClientParams> {
+        use InternalParamValue as V;
+        Ok(ClientParams {
+            remote_port: self.param(InternalParam::RemotePort, V::as_u16)?,
+            ping_timeout: self.param(InternalParam::PingTimeout, V::as_millis)?,
+            send_timeout: self.param(InternalParam::SendTimeout, V::as_millis)?,
+            recv_timeout: self.param(InternalParam::RecvTimeout, V::as_millis)?,
+            work_interval: self.param(InternalParam::WorkInterval, V::as_millis)?,
+            src_ref: self.param(InternalParam::SrcRef, V::as_u16)?,
+            dst_ref: self.param(InternalParam::DstRef, V::as_u16)?,
+            src_tsap: self.param(InternalParam::SrcTSap, V::as_u16)?,
+            pdu_request: self.param(InternalParam::PDURequest, V::as_i32)?,
+        })
+    }
+
+    ///
+    /// 设置连接时的 Ping 超时，为 0 时跳过连接前的 Ping 检测。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - timeout: 超时时间，按毫秒精度设置
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn set_ping_timeout(&self, timeout: Duration) -> Result<()> {
+        self.set_millis_param(InternalParam::PingTimeout, timeout)
+    }
+
+    ///
+    /// 设置 Socket 发送超时。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - timeout: 超时时间，按毫秒精度设置
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn set_send_timeout(&self, timeout: Duration) -> Result<()> {
+        self.set_millis_param(InternalParam::SendTimeout, timeout)
+    }
+
+    ///
+    /// 设置 Socket 接收超时，即等待 PLC 应答的最长时间。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - timeout: 超时时间，按毫秒精度设置
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn set_recv_timeout(&self, timeout: Duration) -> Result<()> {
+        self.set_millis_param(InternalParam::RecvTimeout, timeout)
+    }
+
+    ///
+    /// 设置 Socket 作业间隔。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - interval: 作业间隔，按毫秒精度设置
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn set_work_interval(&self, interval: Duration) -> Result<()> {
+        self.set_millis_param(InternalParam::WorkInterval, interval)
+    }
+
+    ///
+    /// 为低延迟轮询配置较激进的超时与作业间隔。
+    ///
+    /// snap7 已对所有连接启用 TCP_NODELAY，且不提供 Socket 缓冲区大小的设置，
+    /// 因此这里仅调整可用的内部参数: Ping 超时 250ms、发送超时 10ms、
+    /// 接收超时 500ms、作业间隔 1ms。网络较慢或 PLC 负载较高时可能出现超时。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn low_latency_mode(&self) -> Result<()> {
+        self.set_ping_timeout(Duration::from_millis(250))?;
+        self.set_send_timeout(Duration::from_millis(10))?;
+        self.set_recv_timeout(Duration::from_millis(500))?;
+        self.set_work_interval(Duration::from_millis(1))
+    }
+
+    ///
+    /// 设置连接时请求的 PDU 长度，需在连接前调用，下一次连接时生效。
+    ///
+    /// PLC 可能协商出比请求更小的值，连接后可通过 get_pdu_length 查看。较大的 PDU
+    /// 可以显著减少批量读写的往返次数，常见取值: S7-300 为 240，S7-400 为 480，S7-1200/1500 为 960。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - bytes: 请求的 PDU 长度(240..=960，snap7 默认为 480)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 长度超出范围或设置失败
+    ///
+    pub fn request_pdu_size(&self, bytes: i32) -> Result<()> {
+        if !(240..=960).contains(&bytes) {
+            bail!("PDU size {} out of range 240..=960", bytes);
+        }
+        self.set_param(InternalParam::PDURequest, InternalParamValue::I32(bytes))
+    }
+
     ///
     /// 从 PLC 中读取数据, 你可以读取数据块(DB)、输入、输出、内部标志位(Merkers)、定时器和计数器。
     ///
@@ -375,7 +625,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -422,7 +672,126 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 读取任意区域中的单个位，自动使用 word_len=S7WLBit 并换算以比特表示的起点。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - area: 要读取的区域
+    ///  - db_number: 数据块(DB)编号。如果区域不为 S7AreaDB 则被忽略，值为 0。
+    ///  - byte_index: 字节索引
+    ///  - bit_index: 位索引(0..7)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 位的值
+    ///  - Err: 操作失败或位索引无效
+    ///
+    pub fn read_area_bit(
+        &self,
+        area: AreaTable,
+        db_number: i32,
+        byte_index: i32,
+        bit_index: i32,
+    ) -> Result<bool> {
+        let start = Self::bit_start(byte_index, bit_index)?;
+        let mut buff = [0u8; 1];
+        self.read_area(area, db_number, start, 1, WordLenTable::S7WLBit, &mut buff)?;
+        Ok(buff[0] != 0)
+    }
+
+    ///
+    /// 写入任意区域中的单个位，自动使用 word_len=S7WLBit 并换算以比特表示的起点。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - area: 要写入的区域
+    ///  - db_number: 数据块(DB)编号。如果区域不为 S7AreaDB 则被忽略，值为 0。
+    ///  - byte_index: 字节索引
+    ///  - bit_index: 位索引(0..7)
+    ///  - value: 位的新值
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败或位索引无效
+    ///
+    pub fn write_area_bit(
+        &self,
+        area: AreaTable,
+        db_number: i32,
+        byte_index: i32,
+        bit_index: i32,
+        value: bool,
+    ) -> Result<()> {
+        let start = Self::bit_start(byte_index, bit_index)?;
+        let mut buff = [value as u8];
+        self.write_area(area, db_number, start, 1, WordLenTable::S7WLBit, &mut buff)
+    }
+
+    ///
+    /// 轮询 DB 中的某个位，直到其等于期望值或超时，常用于等待握手信号。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - db_number: 数据块(DB)编号
+    ///  - byte_index: 字节索引
+    ///  - bit_index: 位索引(0..7)
+    ///  - expected: 期望值
+    ///  - timeout: 超时时间
+    ///  - poll: 轮询间隔
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 位已等于期望值
+    ///  - Err: 超时、读取失败或位索引无效
+    ///
+    pub fn wait_for_bit(
+        &self,
+        db_number: i32,
+        byte_index: i32,
+        bit_index: i32,
+        expected: bool,
+        timeout: Duration,
+        poll: Duration,
+    ) -> Result<()> {
+        let deadline = std::time::Instant::now() + timeout;
+        loop {
+            if self.read_area_bit(AreaTable::S7AreaDB, db_number, byte_index, bit_index)?
+                == expected
+            {
+                return Ok(());
+            }
+            let now = std::time::Instant::now();
+            if now >= deadline {
+                bail!(
+                    "timed out after {:?} waiting for DB{}.DBX{}.{} to become {}",
+                    timeout,
+                    db_number,
+                    byte_index,
+                    bit_index,
+                    expected
+                );
+            }
+            std::thread::sleep(poll.min(deadline - now));
+        }
+    }
+
+    /// S7WLBit 访问时以比特表示的起点，例如 DB4.DBX 10.3 为 (10*8)+3=83
+    fn bit_start(byte_index: i32, bit_index: i32) -> Result<i32> {
+        Self::check_bit_index(bit_index)?;
+        Ok(byte_index * 8 + bit_index)
+    }
+
+    /// 检查位索引在 0..7 范围内，返回可用于 getters/setters 的索引。
+    fn check_bit_index(bit_index: i32) -> Result<usize> {
+        if !(0..8).contains(&bit_index) {
+            bail!("bit index {} out of range 0..7", bit_index);
+        }
+        Ok(bit_index as usize)
     }
 
     ///
@@ -461,7 +830,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -500,163 +869,482 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
-    /// 从 PLC 输出区读取数据。
+    /// 检查写入范围未超出数据块(DB)的大小后再写入。
     ///
-    /// ```text
-    /// 这是 read_area() 的一个精简函数，它从内部调用了 read_area(), 其内容为:
-    ///     area = S7AreaPA.
-    ///     word_len = S7WLBytes.
-    /// ```
+    /// DB 大小通过 get_ag_block_info() 读取并按 DB 编号缓存，断开连接时清空缓存；
+    /// 连接期间重新下载了更大的 DB 时，需先断开重连。
     ///
     /// **输入参数:**
     ///
-    ///  - start: 开始读取的字节索引
-    ///  - size: 要读取的字节长度
+    ///  - db_number: 要写入的数据块(DB)编号
+    ///  - start: 开始写入的字节索引
     ///  - buff: 待写入数据缓冲区
     ///
     /// **返回值:**
     ///
     ///  - Ok: 操作成功
-    ///  - Err: 操作失败
-    ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_ab_read()。`
+    ///  - Err: 超出 DB 大小时为 Snap7Error::OutOfBounds，其它为操作失败
     ///
-    pub fn ab_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
-        let res = unsafe {
-            Cli_ABRead(
-                self.handle,
-                start as c_int,
-                size as c_int,
-                buff as *mut [u8] as *mut c_void,
-            )
+    pub fn db_write_checked(&self, db_number: i32, start: i32, buff: &mut [u8]) -> Result<()> {
+        if start < 0 {
+            bail!(Snap7Error::client(errCliInvalidParams as i32));
+        }
+        let cached = self.db_sizes.lock().unwrap().get(&db_number).copied();
+        let db_size = match cached {
+            Some(size) => size,
+            None => {
+                let size = self.db_size(db_number)?;
+                self.db_sizes.lock().unwrap().insert(db_number, size);
+                size
+            }
         };
-        if res == 0 {
-            return Ok(());
+        let attempted_end = start as usize + buff.len();
+        if attempted_end > db_size {
+            bail!(Snap7Error::OutOfBounds {
+                db_size,
+                attempted_end,
+            });
         }
-        bail!("{}", Self::error_text(res))
+        self.db_write(db_number, start, buff.len() as i32, buff)
     }
 
     ///
-    /// 向 PLC 输出区写入数据。
-    ///
-    /// ```markdown
-    /// 这是 write_area() 的一个精简函数，它从内部调用了 write_area(), 其内容为:
-    ///     area = S7AreaPA.
-    ///     word_len = S7WLBytes.
-    /// ```
+    /// 按协商的 PDU 大小分块向 PLC DB 区写入全部数据，并返回写入的字节数。
     ///
     /// **输入参数:**
     ///
-    ///  - start: 开始读取的字节索引
-    ///  - size: 要读取的字节长度
+    ///  - db_number: 要写入的数据块(DB)编号
+    ///  - start: 开始写入的字节索引
     ///  - buff: 待写入数据缓冲区
     ///
     /// **返回值:**
     ///
-    ///  - Ok: 操作成功
-    ///  - Err: 操作失败
-    ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_ab_write()。`
-    ///
-    pub fn ab_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
-        let res = unsafe {
-            Cli_ABWrite(
-                self.handle,
-                start as c_int,
-                size as c_int,
-                buff as *mut [u8] as *mut c_void,
-            )
-        };
-        if res == 0 {
-            return Ok(());
+    ///  - Ok: 写入的字节数，等于 buff 长度
+    ///  - Err: 操作失败，若部分分块已写入则为 Snap7Error::PartialWrite，可据此续写
+    ///
+    pub fn db_write_all(&self, db_number: i32, start: i32, buff: &mut [u8]) -> Result<usize> {
+        let chunk_size = self.max_write_chunk()?;
+        let mut written = 0;
+        for chunk in buff.chunks_mut(chunk_size) {
+            let size = chunk.len();
+            if let Err(e) = self.db_write(db_number, start + written as i32, size as i32, chunk) {
+                if written == 0 {
+                    return Err(e);
+                }
+                bail!(Snap7Error::PartialWrite {
+                    written,
+                    message: e.to_string(),
+                })
+            }
+            written += size;
         }
-        bail!("{}", Self::error_text(res))
+        Ok(written)
     }
 
     ///
-    /// 从 PLC 输入区读取数据。
-    ///
-    /// ```text
-    /// 这是 read_area() 的一个精简函数，它从内部调用了 read_area(), 其内容为:
-    ///     area = S7AreaPE.
-    ///     word_len = S7WLBytes.
-    /// ```
+    /// 从 PLC DB 区读取固定 N 个字节到栈上数组，适用于无需分配内存的小数据读取。
     ///
     /// **输入参数:**
     ///
+    ///  - db_number: 要读取的数据块(DB)编号
     ///  - start: 开始读取的字节索引
-    ///  - size: 要读取的字节长度
-    ///  - buff: 待写入数据缓冲区
     ///
     /// **返回值:**
     ///
-    ///  - Ok: 操作成功
+    ///  - Ok: 读取到的 N 字节数组
     ///  - Err: 操作失败
     ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_eb_read()。`
-    ///
-    pub fn eb_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
-        let res = unsafe {
-            Cli_EBRead(
-                self.handle,
-                start as c_int,
-                size as c_int,
-                buff as *mut [u8] as *mut c_void,
-            )
-        };
-        if res == 0 {
-            return Ok(());
-        }
-        bail!("{}", Self::error_text(res))
+    /// # Examples
+    /// ```ignore
+    /// let buff = client.db_read_array::<4>(1, 20)?;
+    /// let value = utils::getters::get_real(&buff, 0);
+    /// ```
+    pub fn db_read_array<const N: usize>(&self, db_number: i32, start: i32) -> Result<[u8; N]> {
+        let mut buff = [0u8; N];
+        self.db_read(db_number, start, N as i32, &mut buff)?;
+        Ok(buff)
     }
 
     ///
-    /// 向 PLC 输入区写入数据。
+    /// 从 PLC DB 区读取一个字节，同时返回该字节和其中指定位的值，便于调试位逻辑。
     ///
-    /// ```markdown
-    /// 这是 write_area() 的一个精简函数，它从内部调用了 write_area(), 其内容为:
-    ///     area = S7AreaPE.
-    ///     word_len = S7WLBytes.
-    /// ```
+    /// **输入参数:**
+    ///
+    ///  - db_number: 要读取的数据块(DB)编号
+    ///  - byte_index: 字节索引
+    ///  - bit_index: 位索引(0..7)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: (字节值, 位值)
+    ///  - Err: 操作失败
+    ///
+    pub fn db_read_byte_and_bit(
+        &self,
+        db_number: i32,
+        byte_index: i32,
+        bit_index: i32,
+    ) -> Result<(u8, bool)> {
+        let bit_index = Self::check_bit_index(bit_index)?;
+        let mut buff = [0u8; 1];
+        self.db_read(db_number, byte_index, 1, &mut buff)?;
+        let bit = getters::get_bool(&buff, 0, bit_index).map_err(Error::msg)?;
+        Ok((buff[0], bit))
+    }
+
+    ///
+    /// 以"读-改-写"方式设置 DB 中某个字节的指定位，不改变同一字节中的其他位。
     ///
     /// **输入参数:**
     ///
-    ///  - start: 开始读取的字节索引
-    ///  - size: 要读取的字节长度
-    ///  - buff: 待写入数据缓冲区
+    ///  - db_number: 数据块(DB)编号
+    ///  - byte_index: 字节索引
+    ///  - bit_index: 位索引(0..7)
+    ///  - value: 位的新值
     ///
     /// **返回值:**
     ///
     ///  - Ok: 操作成功
+    ///  - Err: 操作失败或位索引无效
+    ///
+    /// `注：读和写是两次独立的请求，客户端无法锁定 PLC 内存，期间 PLC 程序或其他客户端对同一字节的修改会被覆盖。`
+    /// `PLC 支持时，建议使用 word_len=S7WLBit 的 write_area() 直接写单个位，它不存在这个竞争窗口。`
+    ///
+    pub fn db_set_bit(
+        &self,
+        db_number: i32,
+        byte_index: i32,
+        bit_index: i32,
+        value: bool,
+    ) -> Result<()> {
+        let bit_index = Self::check_bit_index(bit_index)?;
+        let mut buff = [0u8; 1];
+        self.db_read(db_number, byte_index, 1, &mut buff)?;
+        setters::set_bool(&mut buff, 0, bit_index, value).map_err(Error::msg)?;
+        self.db_write(db_number, byte_index, 1, &mut buff)
+    }
+
+    ///
+    /// 从 PLC DB 区读取一个 S7 TIME 值(4 字节有符号毫秒数)。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - db_number: 数据块(DB)编号
+    ///  - byte_offset: TIME 字段的字节偏移
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 有符号时长，负值表示负的 TIME
     ///  - Err: 操作失败
     ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_eb_write()。`
+    pub fn db_read_time(&self, db_number: i32, byte_offset: i32) -> Result<chrono::Duration> {
+        let buff = self.db_read_array::<4>(db_number, byte_offset)?;
+        getters::get_time_duration(&buff, 0).map_err(Error::msg)
+    }
+
     ///
-    pub fn eb_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
-        let res = unsafe {
-            Cli_EBWrite(
-                self.handle,
-                start as c_int,
-                size as c_int,
-                buff as *mut [u8] as *mut c_void,
-            )
-        };
-        if res == 0 {
-            return Ok(());
+    /// 向 PLC DB 区写入一个 S7 TIME 值(4 字节有符号毫秒数)。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - db_number: 数据块(DB)编号
+    ///  - byte_offset: TIME 字段的字节偏移
+    ///  - value: 待写入的时长，按毫秒精度写入
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败或时长超出 TIME 范围(约 ±24 天)
+    ///
+    pub fn db_write_time(
+        &self,
+        db_number: i32,
+        byte_offset: i32,
+        value: chrono::Duration,
+    ) -> Result<()> {
+        let ms = value.num_milliseconds();
+        if ms < i32::MIN as i64 || ms > i32::MAX as i64 {
+            bail!("duration out of S7 TIME range: {}", value);
         }
-        bail!("{}", Self::error_text(res))
+        let mut buff = (ms as i32).to_be_bytes();
+        self.db_write(db_number, byte_offset, 4, &mut buff)
     }
 
     ///
-    /// 从 PLC 内部标志位(Merkers)读取数据。
+    /// 连续两次读取 DB 中的 REAL 值并比较原始字节，一致时才返回，用于发现关键设定值的撕裂读取。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - db_number: 数据块(DB)编号
+    ///  - byte_offset: REAL 字段的字节偏移
+    ///  - max_retries: 两次读取不一致时的最大重试次数
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 两次读取一致的值
+    ///  - Err: 读取失败，或重试耗尽后仍不一致
+    ///
+    /// `注：单个请求内的读取在 PLC 侧通常已是一致的，该方法只能发现两次请求之间发生的变化，`
+    /// `无法发现在两次请求中都以相同方式撕裂的数据；值持续快速变化时会因重试耗尽而失败。`
+    ///
+    pub fn db_read_real_consistent(
+        &self,
+        db_number: i32,
+        byte_offset: i32,
+        max_retries: usize,
+    ) -> Result<f32> {
+        let mut previous = self.db_read_array::<4>(db_number, byte_offset)?;
+        for _ in 0..=max_retries {
+            let current = self.db_read_array::<4>(db_number, byte_offset)?;
+            if current == previous {
+                return Ok(f32::from_be_bytes(current));
+            }
+            previous = current;
+        }
+        bail!(
+            "DB{}.DBD{} changed between reads {} times in a row",
+            db_number,
+            byte_offset,
+            max_retries + 1
+        )
+    }
+
+    ///
+    /// 按运行时布局读取 DB 并解码为记录。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - db_number: 数据块(DB)编号
+    ///  - layout: 字段布局描述
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 字段名到解码值的映射
+    ///  - Err: 读取或解码失败
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use rust_snap7::utils::layout::{Layout, S7Type};
+    ///
+    /// let layout = Layout::new([("speed", S7Type::Real, 0), ("name", S7Type::String(16), 4)]);
+    /// let record = client.read_record(1, &layout).unwrap();
+    /// println!("{:?}", record["speed"]);
+    /// ```
+    pub fn read_record(
+        &self,
+        db_number: i32,
+        layout: &Layout,
+    ) -> Result<std::collections::HashMap<String, Value>> {
+        let mut buff = vec![0u8; layout.size()];
+        if !buff.is_empty() {
+            self.db_read(db_number, 0, buff.len() as i32, &mut buff)?;
+        }
+        layout.decode(&buff).map_err(Error::msg)
+    }
+
+    ///
+    /// 读取一个变量，同时返回原始字节和解码后的值，便于诊断输出。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - addr: 变量地址，如 `"DB1.DBD4".parse::<TagAddress>()?.with_type(S7Type::Real)`
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: (原始字节, 解码后的值)
+    ///  - Err: 读取或解码失败
+    ///
+    pub fn read_tag_verbose(&self, addr: &TagAddress) -> Result<(Vec<u8>, Value)> {
+        let mut buff = vec![0u8; addr.s7_type.size()];
+        self.read_area(
+            addr.area,
+            addr.db_number as i32,
+            addr.byte_offset as i32,
+            buff.len() as i32,
+            WordLenTable::S7WLByte,
+            &mut buff,
+        )?;
+        let value = addr.s7_type.decode(&buff, 0).map_err(Error::msg)?;
+        Ok((buff, value))
+    }
+
+    ///
+    /// 根据协商的 PDU 长度计算一次 read_multi_vars() 请求最多能容纳的变量数。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 1..=20 之间的变量数
+    ///  - Err: 获取 PDU 长度失败
+    ///
+    pub fn max_multi_var_items(&self) -> Result<usize> {
+        let (mut requested, mut negotiated) = (0, 0);
+        self.get_pdu_length(&mut requested, &mut negotiated)?;
+        // 读请求: 10 字节头 + 2 字节参数，每项 12 字节
+        Ok(((negotiated as usize).saturating_sub(12) / 12).clamp(1, MaxVars as usize))
+    }
+
+    ///
+    /// 批量读取多个变量，请求或应答超出 PDU 时自动拆分重试，因此不受批量大小限制。
+    ///
+    /// 先按 max_multi_var_items() 分组调用 read_multi_vars()；整组因超出 PDU 失败时对半拆分，
+    /// 单项在应答中失败(如应答数据超出 PDU)时改用 read_area() 单独读取。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - tags: 带类型的变量地址
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 与 tags 一一对应的值
+    ///  - Err: 单独读取后仍然失败的第一个变量的错误，或解码失败
+    ///
+    pub fn read_tags_resilient(&self, tags: &[TagAddress]) -> Result<Vec<Value>> {
+        let mut buffers: Vec<Vec<u8>> = tags
+            .iter()
+            .map(|tag| vec![0u8; tag.s7_type.size()])
+            .collect();
+        let max_items = self.max_multi_var_items()?;
+        for (tags, buffers) in tags.chunks(max_items).zip(buffers.chunks_mut(max_items)) {
+            self.read_tags_batch(tags, buffers)?;
+        }
+        tags.iter()
+            .zip(&buffers)
+            .map(|(tag, buff)| {
+                tag.s7_type
+                    .decode(buff, 0)
+                    .map_err(Error::msg)
+                    .with_context(|| format!("decoding {:?}", tag))
+            })
+            .collect()
+    }
+
+    fn read_tags_batch(&self, tags: &[TagAddress], buffers: &mut [Vec<u8>]) -> Result<()> {
+        let mut items: Vec<TS7DataItem> = tags
+            .iter()
+            .zip(buffers.iter_mut())
+            .map(|(tag, buff)| TS7DataItem {
+                Area: tag.area as c_int,
+                WordLen: WordLenTable::S7WLByte as c_int,
+                Result: 0,
+                DBNumber: tag.db_number as c_int,
+                Start: tag.byte_offset as c_int,
+                Amount: buff.len() as c_int,
+                pdata: buff.as_mut_ptr() as *mut c_void,
+            })
+            .collect();
+        if let Err(e) = self.read_multi_vars(&mut items, tags.len() as i32) {
+            let code = e.downcast_ref::<Snap7Error>().and_then(Snap7Error::code);
+            let overflow = [errCliSizeOverPDU as i32, errCliTooManyItems as i32];
+            if !code.is_some_and(|code| overflow.contains(&code)) {
+                return Err(e);
+            }
+            if tags.len() > 1 {
+                let half = tags.len() / 2;
+                let (first, second) = buffers.split_at_mut(half);
+                self.read_tags_batch(&tags[..half], first)?;
+                return self.read_tags_batch(&tags[half..], second);
+            }
+            items[0].Result = code.unwrap_or_default();
+        }
+        for ((tag, buff), item) in tags.iter().zip(buffers.iter_mut()).zip(&items) {
+            if item.Result != 0 {
+                self.read_area(
+                    tag.area,
+                    tag.db_number as i32,
+                    tag.byte_offset as i32,
+                    buff.len() as i32,
+                    WordLenTable::S7WLByte,
+                    buff,
+                )
+                .with_context(|| format!("reading {:?}", tag))?;
+            }
+        }
+        Ok(())
+    }
+
+    ///
+    /// 读取 DB 的一段区域并格式化为十六进制转储字符串(类似 xxd)，便于调试。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - db_number: 数据块(DB)编号
+    ///  - start: 开始读取的字节索引，同时作为转储的起始偏移
+    ///  - size: 要读取的字节长度
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 每行 16 字节的十六进制与 ASCII 对照文本
+    ///  - Err: 操作失败
+    ///
+    pub fn db_hexdump(&self, db_number: i32, start: i32, size: i32) -> Result<String> {
+        if start < 0 || size < 0 {
+            bail!(Snap7Error::client(errCliInvalidParams as i32));
+        }
+        let mut buff = vec![0u8; size as usize];
+        if !buff.is_empty() {
+            self.db_read(db_number, start, size, &mut buff)?;
+        }
+        Ok(hexdump_at(&buff, start as usize))
+    }
+
+    ///
+    /// 向 PLC 输出区写入一个位并回读校验。
+    ///
+    /// `注：校验读取的是输出映像区，而不是物理输出状态；物理输出仍可能被 PLC 程序覆盖。`
+    ///
+    /// **输入参数:**
+    ///
+    ///  - byte_index: 字节索引
+    ///  - bit_index: 位索引(0..7)
+    ///  - value: 待写入的值
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 写入并校验成功
+    ///  - Err: 操作失败或回读值不一致(Snap7Error::BitMismatch)
+    ///
+    pub fn write_output_bit_verified(
+        &self,
+        byte_index: i32,
+        bit_index: i32,
+        value: bool,
+    ) -> Result<()> {
+        self.write_bit_verified(AreaTable::S7AreaPA, byte_index, bit_index, value)
+    }
+
+    ///
+    /// 向 PLC 标志区(M)写入一个位并回读校验。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - byte_index: 字节索引
+    ///  - bit_index: 位索引(0..7)
+    ///  - value: 待写入的值
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 写入并校验成功
+    ///  - Err: 操作失败或回读值不一致(Snap7Error::BitMismatch)
+    ///
+    pub fn write_merker_bit_verified(
+        &self,
+        byte_index: i32,
+        bit_index: i32,
+        value: bool,
+    ) -> Result<()> {
+        self.write_bit_verified(AreaTable::S7AreaMK, byte_index, bit_index, value)
+    }
+
+    ///
+    /// 从 PLC 输出区读取数据。
     ///
     /// ```text
     /// 这是 read_area() 的一个精简函数，它从内部调用了 read_area(), 其内容为:
-    ///     area = S7AreaMK.
+    ///     area = S7AreaPA.
     ///     word_len = S7WLBytes.
     /// ```
     ///
@@ -671,11 +1359,11 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_mb_read()。`
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_ab_read()。`
     ///
-    pub fn mb_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+    pub fn ab_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
         let res = unsafe {
-            Cli_MBRead(
+            Cli_ABRead(
                 self.handle,
                 start as c_int,
                 size as c_int,
@@ -685,15 +1373,15 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
-    /// 向 PLC 内部标志位(Merkers)写入数据。
+    /// 向 PLC 输出区写入数据。
     ///
     /// ```markdown
     /// 这是 write_area() 的一个精简函数，它从内部调用了 write_area(), 其内容为:
-    ///     area = S7AreaMK.
+    ///     area = S7AreaPA.
     ///     word_len = S7WLBytes.
     /// ```
     ///
@@ -708,11 +1396,11 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_mb_write()。`
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_ab_write()。`
     ///
-    pub fn mb_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+    pub fn ab_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
         let res = unsafe {
-            Cli_MBWrite(
+            Cli_ABWrite(
                 self.handle,
                 start as c_int,
                 size as c_int,
@@ -722,16 +1410,16 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
-    /// 读取 PLC 定时器数据。
+    /// 从 PLC 输入区读取数据。
     ///
     /// ```text
     /// 这是 read_area() 的一个精简函数，它从内部调用了 read_area(), 其内容为:
-    ///     area = S7AreaTM.
-    ///     word_len = S7WLTimer.
+    ///     area = S7AreaPE.
+    ///     word_len = S7WLBytes.
     /// ```
     ///
     /// **输入参数:**
@@ -745,12 +1433,11 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_tm_read()。`
-    /// `    缓冲区大小 = size * 2`
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_eb_read()。`
     ///
-    pub fn tm_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+    pub fn eb_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
         let res = unsafe {
-            Cli_TMRead(
+            Cli_EBRead(
                 self.handle,
                 start as c_int,
                 size as c_int,
@@ -760,16 +1447,16 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
-    /// 向 PLC 定时器写入数据。
+    /// 向 PLC 输入区写入数据。
     ///
     /// ```markdown
     /// 这是 write_area() 的一个精简函数，它从内部调用了 write_area(), 其内容为:
-    ///     area = S7AreaTM.
-    ///     word_len = S7WLTimer.
+    ///     area = S7AreaPE.
+    ///     word_len = S7WLBytes.
     /// ```
     ///
     /// **输入参数:**
@@ -783,12 +1470,11 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_tm_write()。`
-    /// `    缓冲区大小 = size * 2`
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_eb_write()。`
     ///
-    pub fn tm_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+    pub fn eb_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
         let res = unsafe {
-            Cli_TMWrite(
+            Cli_EBWrite(
                 self.handle,
                 start as c_int,
                 size as c_int,
@@ -798,16 +1484,16 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
-    /// 读取 PLC 计数器数据。
+    /// 从 PLC 内部标志位(Merkers)读取数据。
     ///
     /// ```text
     /// 这是 read_area() 的一个精简函数，它从内部调用了 read_area(), 其内容为:
-    ///     area = S7AreaCT.
-    ///     word_len = S7WLCounter.
+    ///     area = S7AreaMK.
+    ///     word_len = S7WLBytes.
     /// ```
     ///
     /// **输入参数:**
@@ -821,12 +1507,11 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_ct_read()。`
-    /// `    缓冲区大小 = size * 2`
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_mb_read()。`
     ///
-    pub fn ct_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+    pub fn mb_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
         let res = unsafe {
-            Cli_CTRead(
+            Cli_MBRead(
                 self.handle,
                 start as c_int,
                 size as c_int,
@@ -836,16 +1521,16 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
-    /// 向 PLC 计数器写入数据。
+    /// 向 PLC 内部标志位(Merkers)写入数据。
     ///
     /// ```markdown
     /// 这是 write_area() 的一个精简函数，它从内部调用了 write_area(), 其内容为:
-    ///     area = S7AreaCT.
-    ///     word_len = S7WLCounter.
+    ///     area = S7AreaMK.
+    ///     word_len = S7WLBytes.
     /// ```
     ///
     /// **输入参数:**
@@ -859,12 +1544,11 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_ct_write()。`
-    /// `    缓冲区大小 = size * 2`
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_mb_write()。`
     ///
-    pub fn ct_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+    pub fn mb_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
         let res = unsafe {
-            Cli_CTWrite(
+            Cli_MBWrite(
                 self.handle,
                 start as c_int,
                 size as c_int,
@@ -874,24 +1558,220 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
-    /// 在一次调用中从 PLC 读取不同区域的数据。
+    /// 读取 PLC 定时器数据。
+    ///
+    /// ```text
+    /// 这是 read_area() 的一个精简函数，它从内部调用了 read_area(), 其内容为:
+    ///     area = S7AreaTM.
+    ///     word_len = S7WLTimer.
+    /// ```
     ///
     /// **输入参数:**
     ///
-    ///  - item: TS7DataItem 数组
-    ///  - items_count: 要读取的区域数量
+    ///  - start: 开始读取的字节索引
+    ///  - size: 要读取的字节长度
+    ///  - buff: 待写入数据缓冲区
     ///
     /// **返回值:**
     ///
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    /// `注：由于涉及到不同区域的变量，这个函数没有分割功能，所以最大数据量不能超过PDU的大小。`
-    /// `因此，这个函数没有对应的异步函数。当你有许多非连续的小变量需要读取时，这个函数的优势就会变得很大。`
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_tm_read()。`
+    /// `    缓冲区大小 = size * 2`
+    ///
+    pub fn tm_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        let res = unsafe {
+            Cli_TMRead(
+                self.handle,
+                start as c_int,
+                size as c_int,
+                buff as *mut [u8] as *mut c_void,
+            )
+        };
+        if res == 0 {
+            return Ok(());
+        }
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 读取 PLC 定时器并解析为时长。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - start: 起始定时器编号
+    ///  - count: 要读取的定时器数量
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 每个定时器的 S5TIME 时长
+    ///  - Err: 操作失败或数据不是有效的 S5TIME
+    ///
+    pub fn read_timers(&self, start: i32, count: i32) -> Result<Vec<std::time::Duration>> {
+        let mut buff = vec![0u8; count.max(0) as usize * 2];
+        self.tm_read(start, count, &mut buff)?;
+        (0..buff.len())
+            .step_by(2)
+            .map(|i| getters::get_s5time_duration(&buff, i).map_err(Error::msg))
+            .collect()
+    }
+
+    ///
+    /// 向 PLC 定时器写入数据。
+    ///
+    /// ```markdown
+    /// 这是 write_area() 的一个精简函数，它从内部调用了 write_area(), 其内容为:
+    ///     area = S7AreaTM.
+    ///     word_len = S7WLTimer.
+    /// ```
+    ///
+    /// **输入参数:**
+    ///
+    ///  - start: 开始读取的字节索引
+    ///  - size: 要读取的字节长度
+    ///  - buff: 待写入数据缓冲区
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_tm_write()。`
+    /// `    缓冲区大小 = size * 2`
+    ///
+    pub fn tm_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        let res = unsafe {
+            Cli_TMWrite(
+                self.handle,
+                start as c_int,
+                size as c_int,
+                buff as *mut [u8] as *mut c_void,
+            )
+        };
+        if res == 0 {
+            return Ok(());
+        }
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 读取 PLC 计数器数据。
+    ///
+    /// ```text
+    /// 这是 read_area() 的一个精简函数，它从内部调用了 read_area(), 其内容为:
+    ///     area = S7AreaCT.
+    ///     word_len = S7WLCounter.
+    /// ```
+    ///
+    /// **输入参数:**
+    ///
+    ///  - start: 开始读取的字节索引
+    ///  - size: 要读取的字节长度
+    ///  - buff: 待写入数据缓冲区
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_ct_read()。`
+    /// `    缓冲区大小 = size * 2`
+    ///
+    pub fn ct_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        let res = unsafe {
+            Cli_CTRead(
+                self.handle,
+                start as c_int,
+                size as c_int,
+                buff as *mut [u8] as *mut c_void,
+            )
+        };
+        if res == 0 {
+            return Ok(());
+        }
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 读取 PLC 计数器并解析为计数值。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - start: 起始计数器编号
+    ///  - count: 要读取的计数器数量
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 每个计数器的值(0..999)
+    ///  - Err: 操作失败或数据不是有效的 BCD 码
+    ///
+    pub fn read_counters(&self, start: i32, count: i32) -> Result<Vec<u16>> {
+        let mut buff = vec![0u8; count.max(0) as usize * 2];
+        self.ct_read(start, count, &mut buff)?;
+        (0..buff.len())
+            .step_by(2)
+            .map(|i| getters::get_counter(&buff, i).map_err(Error::msg))
+            .collect()
+    }
+
+    ///
+    /// 向 PLC 计数器写入数据。
+    ///
+    /// ```markdown
+    /// 这是 write_area() 的一个精简函数，它从内部调用了 write_area(), 其内容为:
+    ///     area = S7AreaCT.
+    ///     word_len = S7WLCounter.
+    /// ```
+    ///
+    /// **输入参数:**
+    ///
+    ///  - start: 开始读取的字节索引
+    ///  - size: 要读取的字节长度
+    ///  - buff: 待写入数据缓冲区
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    /// `注：如果你需要传输一个大的数据，你可以考虑使用异步的 as_ct_write()。`
+    /// `    缓冲区大小 = size * 2`
+    ///
+    pub fn ct_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        let res = unsafe {
+            Cli_CTWrite(
+                self.handle,
+                start as c_int,
+                size as c_int,
+                buff as *mut [u8] as *mut c_void,
+            )
+        };
+        if res == 0 {
+            return Ok(());
+        }
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 在一次调用中从 PLC 读取不同区域的数据。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - item: TS7DataItem 数组
+    ///  - items_count: 要读取的区域数量
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    /// `注：由于涉及到不同区域的变量，这个函数没有分割功能，所以最大数据量不能超过PDU的大小。`
+    /// `因此，这个函数没有对应的异步函数。当你有许多非连续的小变量需要读取时，这个函数的优势就会变得很大。`
     ///
     /// # Examples
     /// ```ignore
@@ -931,7 +1811,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -958,7 +1838,206 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 读取输入(I)和输出(Q)过程映像，用于诊断快照。
+    ///
+    /// 两个区域能放入一个 PDU 时通过一次 read_multi_vars() 读取以减小时间偏差，
+    /// 否则分别调用 eb_read() 和 ab_read()。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - input_size: 输入映像字节数
+    ///  - output_size: 输出映像字节数
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: (输入映像, 输出映像)
+    ///  - Err: 操作失败
+    ///
+    /// `注：即使使用一次请求，PLC 也不保证两个区域来自同一扫描周期，不能视为真正的原子读取。`
+    ///
+    pub fn read_process_image(
+        &self,
+        input_size: usize,
+        output_size: usize,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let mut inputs = vec![0u8; input_size];
+        let mut outputs = vec![0u8; output_size];
+        let (mut requested, mut negotiated) = (0, 0);
+        self.get_pdu_length(&mut requested, &mut negotiated)?;
+        // 应答报文头 18 字节(含 2 字节参数)，每个数据项 4 字节项头，奇数长度补 1 字节
+        let reply_size = 18 + 4 + input_size + input_size % 2 + 4 + output_size;
+        if input_size == 0 || output_size == 0 || reply_size > negotiated as usize {
+            if input_size > 0 {
+                self.eb_read(0, input_size as i32, &mut inputs)?;
+            }
+            if output_size > 0 {
+                self.ab_read(0, output_size as i32, &mut outputs)?;
+            }
+            return Ok((inputs, outputs));
+        }
+        let mut items = [
+            TS7DataItem {
+                Area: AreaTable::S7AreaPE as c_int,
+                WordLen: WordLenTable::S7WLByte as c_int,
+                Result: 0,
+                DBNumber: 0,
+                Start: 0,
+                Amount: input_size as c_int,
+                pdata: inputs.as_mut_ptr() as *mut c_void,
+            },
+            TS7DataItem {
+                Area: AreaTable::S7AreaPA as c_int,
+                WordLen: WordLenTable::S7WLByte as c_int,
+                Result: 0,
+                DBNumber: 0,
+                Start: 0,
+                Amount: output_size as c_int,
+                pdata: outputs.as_mut_ptr() as *mut c_void,
+            },
+        ];
+        self.read_multi_vars(&mut items, 2)?;
+        for item in &items {
+            if item.Result != 0 {
+                bail!(Snap7Error::client(item.Result));
+            }
+        }
+        Ok((inputs, outputs))
+    }
+
+    ///
+    /// 依次读取编号连续、布局相同的多个 DB(如每台设备一个 DB)，并从各 DB 的起始处解码为结构体。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - first_db: 第一个数据块(DB)编号
+    ///  - last_db: 最后一个数据块(DB)编号(包含)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 按 DB 编号顺序排列的结果，单个 DB 读取或解码失败只记录在对应位置
+    ///  - Err: 编号范围无效
+    ///
+    pub fn read_db_range<T: S7Struct>(
+        &self,
+        first_db: i32,
+        last_db: i32,
+    ) -> Result<Vec<Result<T>>> {
+        if first_db > last_db {
+            bail!("invalid DB range {}..={}", first_db, last_db);
+        }
+        let mut buff = vec![0u8; T::SIZE];
+        Ok((first_db..=last_db)
+            .map(|db_number| {
+                self.db_read(db_number, 0, T::SIZE as i32, &mut buff)
+                    .and_then(|_| T::from_bytes(&buff).map_err(Error::msg))
+                    .with_context(|| format!("DB{}", db_number))
+            })
+            .collect())
+    }
+
+    ///
+    /// 列出 PLC 中所有的数据块(DB)，并逐个读取大小和是否为优化的 DB，便于备份和迁移工具盘点。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 按 DB 编号排列的结果，单个 DB 失败时对应项为带 "DB{n}" 上下文的错误
+    ///  - Err: 列出 DB 失败
+    ///
+    pub fn db_directory(&self) -> Result<Vec<Result<DbEntry>>> {
+        let mut buff: TS7BlocksOfType = [0; 8192];
+        let mut items_count = buff.len() as i32;
+        self.list_blocks_of_type(BlockType::BlockDB, &mut buff, &mut items_count)?;
+        let mut numbers = buff[..items_count.clamp(0, buff.len() as i32) as usize].to_vec();
+        numbers.sort_unstable();
+        Ok(numbers
+            .into_iter()
+            .map(|number| {
+                let db_number = number as i32;
+                self.db_size(db_number)
+                    .and_then(|size| {
+                        Ok(DbEntry {
+                            number,
+                            size: size as u32,
+                            optimized: self.db_is_optimized(db_number)?,
+                        })
+                    })
+                    .with_context(|| format!("DB{}", number))
+            })
+            .collect())
+    }
+
+    ///
+    /// 批量写入标签，并以尽可能少的 read_multi_vars() 调用回读校验。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - writes: 待写入的标签
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 与 writes 一一对应的结果，写入失败返回对应错误，回读不一致返回 Snap7Error::DataMismatch
+    ///  - Err: 无法获取 PDU 长度
+    ///
+    pub fn write_tags_verified(&self, writes: &[TagWrite]) -> Result<Vec<Result<()>>> {
+        let mut results: Vec<Result<()>> = writes
+            .iter()
+            .map(|tag| {
+                let mut data = tag.data.clone();
+                self.write_area(
+                    tag.area,
+                    tag.db_number,
+                    tag.start,
+                    data.len() as i32,
+                    WordLenTable::S7WLByte,
+                    &mut data,
+                )
+            })
+            .collect();
+
+        // 读取请求: 10 字节头 + 2 字节参数，每项 12 字节
+        // 读取应答: 12 字节头 + 2 字节参数，每项 4 字节头并按偶数对齐
+        let (mut requested, mut negotiated) = (0, 0);
+        self.get_pdu_length(&mut requested, &mut negotiated)?;
+        let pdu = if negotiated > 0 { negotiated } else { 240 };
+        let max_items = ((pdu as usize).saturating_sub(12) / 12).clamp(1, MaxVars as usize);
+        let budget = (pdu as usize).saturating_sub(14);
+
+        let mut group: Vec<usize> = Vec::new();
+        let mut used = 0;
+        let written: Vec<usize> = (0..writes.len()).filter(|i| results[*i].is_ok()).collect();
+        for index in written {
+            let cost = 4 + writes[index].data.len().div_ceil(2) * 2;
+            if cost > budget {
+                let tag = &writes[index];
+                let mut actual = vec![0u8; tag.data.len()];
+                results[index] = self
+                    .read_area(
+                        tag.area,
+                        tag.db_number,
+                        tag.start,
+                        actual.len() as i32,
+                        WordLenTable::S7WLByte,
+                        &mut actual,
+                    )
+                    .and_then(|_| Self::compare_read_back(&tag.data, actual));
+                continue;
+            }
+            if group.len() == max_items || used + cost > budget {
+                self.verify_group(writes, &group, &mut results);
+                group.clear();
+                used = 0;
+            }
+            group.push(index);
+            used += cost;
+        }
+        if !group.is_empty() {
+            self.verify_group(writes, &group, &mut results);
+        }
+        Ok(results)
     }
 
     ///
@@ -978,7 +2057,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1019,7 +2098,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1053,7 +2132,55 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 通过 get_ag_block_info() 读取数据块(DB)的大小。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - db_number: 数据块(DB)编号
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: DB 大小(字节，即 MC7Size)
+    ///  - Err: 操作失败
+    ///
+    pub fn db_size(&self, db_number: i32) -> Result<usize> {
+        let mut info = TS7BlockInfo::default();
+        self.get_ag_block_info(BlockType::BlockDB, db_number, &mut info)?;
+        Ok(info.MC7Size.max(0) as usize)
+    }
+
+    ///
+    /// 判断数据块(DB)是否为"优化的块访问"，优化的 DB 不能通过绝对地址读写。
+    ///
+    /// 块信息中没有可靠的优化标志，因此在 DB 首字节做一次探测读取: 读取成功为标准 DB，
+    /// 返回地址越界错误(S7-1200/1500 拒绝绝对寻址时的应答)则为优化 DB，其他错误(如 DB 不存在)原样返回。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - db_number: 数据块(DB)编号
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: true 为优化 DB，false 为标准 DB
+    ///  - Err: DB 不存在或操作失败
+    ///
+    pub fn db_is_optimized(&self, db_number: i32) -> Result<bool> {
+        let mut buff = [0u8; 1];
+        match self.db_read(db_number, 0, 1, &mut buff) {
+            std::result::Result::Ok(()) => Ok(false),
+            Err(e)
+                if e.downcast_ref::<Snap7Error>()
+                    .and_then(Snap7Error::code)
+                    .is_some_and(|code| code as u32 == errCliAddressOutOfRange) =>
+            {
+                Ok(true)
+            }
+            Err(e) => Err(e),
+        }
     }
 
     ///
@@ -1087,7 +2214,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1131,28 +2258,63 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
-    /// 从 AG 上传一个区块主体。只将区块主体复制到用户缓冲区。
+    /// 上传一个完整区块并解析其 PG 区块信息。
+    ///
+    /// 先通过 get_ag_block_info() 获取区块的装载大小以分配缓冲区，再调用 full_upload() 与
+    /// get_pg_block_info()。
     ///
     /// **输入参数:**
     ///
-    ///  - block_type: 要获取的区块类型
-    ///  - block_num: 要获取的区块号
-    ///  - buff: 用户缓冲区
-    ///  - size: 在输入中表示缓冲区大小，在输出中表示上传的字节数
+    ///  - block_type: 要上传的区块类型
+    ///  - block_num: 要上传的区块号
     ///
     /// **返回值:**
     ///
-    ///  - Ok: 操作成功
+    ///  - Ok: (上传的区块数据, 区块信息)
     ///  - Err: 操作失败
     ///
-    /// # Examples
-    /// ```ignore
-    ///    let mut buff = [0; 4096];
-    ///    let mut size = buff.len() as i32;
+    pub fn upload_and_describe(
+        &self,
+        block_type: BlockType,
+        block_num: i32,
+    ) -> Result<(Vec<u8>, TS7BlockInfo)> {
+        let mut info = TS7BlockInfo::default();
+        self.get_ag_block_info(block_type, block_num, &mut info)?;
+
+        let load_size = info.LoadSize;
+        let mut buff = vec![0u8; load_size.max(0) as usize];
+        let mut size = buff.len() as i32;
+        self.full_upload(block_type, block_num, &mut buff, &mut size)?;
+        buff.truncate(size.max(0) as usize);
+
+        let mut info = TS7BlockInfo::default();
+        self.get_pg_block_info(&mut buff, &mut info, size)?;
+        Ok((buff, info))
+    }
+
+    ///
+    /// 从 AG 上传一个区块主体。只将区块主体复制到用户缓冲区。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - block_type: 要获取的区块类型
+    ///  - block_num: 要获取的区块号
+    ///  - buff: 用户缓冲区
+    ///  - size: 在输入中表示缓冲区大小，在输出中表示上传的字节数
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    /// # Examples
+    /// ```ignore
+    ///    let mut buff = [0; 4096];
+    ///    let mut size = buff.len() as i32;
     ///    client.upload(BlockType::BlockSDB, 0, &mut buff, &mut size);
     /// ```
     ///
@@ -1175,7 +2337,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1206,7 +2368,35 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 从 Read 数据源读取区块并下载到 AG，例如直接从文件下载。
+    ///
+    /// 这不是流式传输：整个区块先读入一个 size 字节的缓冲区，再调用 download()，
+    /// 内存占用等于 size。size 不能超过 snap7 的区块缓冲区(65536 字节)。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - block_num: 新区块编号，或 -1
+    ///  - reader: 区块数据源
+    ///  - size: 区块大小(字节)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: size 超出范围，数据源不足 size 字节，或下载失败
+    ///
+    pub fn download_reader(&self, block_num: i32, mut reader: impl Read, size: i32) -> Result<()> {
+        if size <= 0 || size > MAX_BLOCK_SIZE {
+            bail!(Snap7Error::client(errCliInvalidBlockSize as i32));
+        }
+        let mut buff = vec![0u8; size as usize];
+        reader
+            .read_exact(&mut buff)
+            .with_context(|| format!("reading {} bytes of block data", size))?;
+        self.download(block_num, &mut buff, size)
     }
 
     ///
@@ -1229,7 +2419,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1259,7 +2449,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1282,7 +2472,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1302,7 +2492,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1322,7 +2512,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1338,7 +2528,111 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 以 Unix 时间戳(秒)设置 PLC 的日期和时间，按 UTC 写入，不做时区转换。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - unix_secs: Unix 时间戳，须在 DATE_AND_TIME 可表示的 1990-01-01 至 2089-12-31 之间
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 时间戳超出范围或操作失败
+    ///
+    pub fn set_plc_time_epoch(&self, unix_secs: i64) -> Result<()> {
+        let mut date_time = Self::date_time_from_epoch(unix_secs)?;
+        self.set_plc_date_time(&mut date_time)
+    }
+
+    fn date_time_from_epoch(unix_secs: i64) -> Result<DateTime> {
+        use chrono::{Datelike, Timelike};
+
+        // 1990-01-01T00:00:00Z ..= 2089-12-31T23:59:59Z
+        if !(631_152_000..=3_786_911_999).contains(&unix_secs) {
+            bail!(
+                "epoch {} outside the DATE_AND_TIME range 1990..=2089",
+                unix_secs
+            );
+        }
+        let time = chrono::DateTime::from_timestamp(unix_secs, 0)
+            .ok_or_else(|| anyhow!("invalid epoch: {}", unix_secs))?
+            .naive_utc();
+        Ok(DateTime {
+            tm_sec: time.second() as c_int,
+            tm_min: time.minute() as c_int,
+            tm_hour: time.hour() as c_int,
+            tm_mday: time.day() as c_int,
+            tm_mon: time.month0() as c_int,
+            tm_year: time.year() - 1900,
+            tm_wday: time.weekday().num_days_from_sunday() as c_int,
+            tm_yday: time.ordinal0() as c_int,
+            tm_isdst: 0,
+        })
+    }
+
+    ///
+    /// 读取 PLC 的日期和时间。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: PLC 时间(PLC 时钟不带时区信息)
+    ///  - Err: 操作失败或 PLC 返回了无效的日期时间
+    ///
+    pub fn plc_time(&self) -> Result<chrono::NaiveDateTime> {
+        let mut date_time = DateTime::default();
+        self.get_plc_date_time(&mut date_time)?;
+        chrono::NaiveDate::from_ymd_opt(
+            date_time.tm_year + 1900,
+            (date_time.tm_mon + 1) as u32,
+            date_time.tm_mday as u32,
+        )
+        .and_then(|date| {
+            date.and_hms_opt(
+                date_time.tm_hour as u32,
+                date_time.tm_min as u32,
+                date_time.tm_sec as u32,
+            )
+        })
+        .ok_or_else(|| anyhow!("invalid PLC date time: {:?}", date_time))
+    }
+
+    ///
+    /// 计算 PLC 时钟相对 PC 当前 UTC 时间的偏差(PLC 时间 - UTC 时间)。
+    ///
+    /// PLC 时钟不带时区信息，该函数假定 PLC 按 UTC 设置(如 set_plc_time_epoch())；
+    /// PLC 按本地时间设置时请使用 clock_drift_from()。PLC 时钟精度为 1 秒。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 时钟偏差，正值表示 PLC 时钟超前
+    ///  - Err: 操作失败
+    ///
+    pub fn clock_drift(&self) -> Result<chrono::Duration> {
+        self.clock_drift_from(chrono::Utc::now().naive_utc())
+    }
+
+    ///
+    /// 计算 PLC 时钟相对参考时间的偏差(PLC 时间 - 参考时间)。
+    ///
+    /// 参考时间需与 PLC 时钟使用同一时区：PLC 按本地时间设置(如 set_plc_system_date_time())时
+    /// 传入 `chrono::Local::now().naive_local()`。PLC 时钟精度为 1 秒。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - reference: 参考时间
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 时钟偏差，正值表示 PLC 时钟超前
+    ///  - Err: 操作失败
+    ///
+    pub fn clock_drift_from(&self, reference: chrono::NaiveDateTime) -> Result<chrono::Duration> {
+        let plc_time = self.plc_time()?;
+        Ok(plc_time - reference)
     }
 
     ///
@@ -1369,7 +2663,33 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 依次读取给定 ID 的所有局部系统状态列表(部分列表)。
+    ///
+    /// 从 INDEX 0 开始逐个递增，直到 PLC 报告该 INDEX 不可用为止。
+    ///
+    /// **输入参数:**
+    ///
+    /// - id: 列表 ID
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 按 INDEX 顺序排列的各部分列表数据(不含 SZL 头)
+    ///  - Err: 操作失败
+    ///
+    pub fn read_szl_all(&self, id: i32) -> Result<Vec<Vec<u8>>> {
+        let mut lists = Vec::new();
+        for index in 0..=u16::MAX as i32 {
+            match self.read_szl_records(id, index) {
+                std::result::Result::Ok((data, _, _)) => lists.push(data),
+                Err(e) if Self::is_item_not_available(&e) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(lists)
     }
 
     ///
@@ -1396,7 +2716,45 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 读取局部系统状态列表的目录，返回 CPU 支持的全部 SZL ID。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: SZL ID 列表
+    ///  - Err: 操作失败
+    ///
+    pub fn szl_catalog(&self) -> Result<Vec<u16>> {
+        let mut list = TS7SZLList {
+            Header: SZL_HEADER {
+                LENTHDR: 0,
+                N_DR: 0,
+            },
+            List: [0; 8190],
+        };
+        let mut items_count = 8190;
+        self.read_szl_list(&mut list, &mut items_count)?;
+        let ids = list.List;
+        Ok(ids[..items_count as usize].to_vec())
+    }
+
+    ///
+    /// 读取局部系统状态列表的目录，并附带每个 SZL ID 的文字说明。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: (SZL ID, 说明) 列表，未知的 ID 说明为 "Unknown"
+    ///  - Err: 操作失败
+    ///
+    pub fn szl_catalog_described(&self) -> Result<Vec<(u16, &'static str)>> {
+        Ok(self
+            .szl_catalog()?
+            .into_iter()
+            .map(|id| (id, szl_description(id).unwrap_or("Unknown")))
+            .collect())
     }
 
     ///
@@ -1416,7 +2774,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1436,7 +2794,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1456,7 +2814,175 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 获取 CP（通信处理器）信息，以命名字段返回。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: CpInfo 结构体
+    ///  - Err: 操作失败
+    ///
+    pub fn cp_info(&self) -> Result<CpInfo> {
+        let mut info = TS7CpInfo {
+            MaxPduLengt: 0,
+            MaxConnections: 0,
+            MaxMpiRate: 0,
+            MaxBusRate: 0,
+        };
+        self.get_cp_info(&mut info)?;
+        Ok(CpInfo {
+            max_pdu_length: info.MaxPduLengt,
+            max_connections: info.MaxConnections,
+            max_mpi_rate: info.MaxMpiRate,
+            max_bus_rate: info.MaxBusRate,
+        })
+    }
+
+    ///
+    /// 读取 SZL 0x001C 并解码组件标识(自动化系统名称、序列号、设备标识等)。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: ComponentIdentification 结构体
+    ///  - Err: 操作失败
+    ///
+    pub fn component_identification(&self) -> Result<ComponentIdentification> {
+        let (data, record_len, count) = self.read_szl_records(0x001C, 0)?;
+        Ok(ComponentIdentification::from_szl(&data, record_len, count))
+    }
+
+    ///
+    /// 读取 SZL 0x0013 并解码 CPU 工作存储器和装载存储器的总大小与空闲大小。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: MemoryInfo 结构体
+    ///  - Err: 操作失败，或 CPU 不提供存储器信息
+    ///
+    pub fn memory_info(&self) -> Result<MemoryInfo> {
+        let (data, record_len, count) = self.read_szl_records(0x0013, 0)?;
+        MemoryInfo::from_szl(&data, record_len, count)
+            .ok_or_else(|| anyhow!("the CPU does not provide memory information (SZL 0x0013)"))
+    }
+
+    ///
+    /// 读取 SZL 0x0222 中 OB1 的启动信息，获取当前、最短和最长扫描周期时间。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: CycleTime 结构体
+    ///  - Err: 操作失败，或 CPU 不提供 OB1 的中断状态
+    ///
+    pub fn cycle_time(&self) -> Result<CycleTime> {
+        let (data, record_len, count) = self.read_szl_records(0x0222, 0x0001)?;
+        CycleTime::from_szl(&data, record_len, count)
+            .ok_or_else(|| anyhow!("the CPU does not provide cycle time information (SZL 0x0222)"))
+    }
+
+    ///
+    /// 读取 SZL 0x0074，获取 CPU 各指示灯(RUN、STOP、SF、MAINT 等)的点亮和闪烁状态。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 指示灯状态列表
+    ///  - Err: 操作失败，或 CPU 不提供指示灯状态
+    ///
+    pub fn led_states(&self) -> Result<Vec<LedState>> {
+        let (data, record_len, count) = self.read_szl_records(0x0074, 0x0000)?;
+        LedState::from_szl(&data, record_len, count)
+            .ok_or_else(|| anyhow!("the CPU does not provide LED states (SZL 0x0074)"))
+    }
+
+    ///
+    /// 读取 SZL 0x0014 并解码 CPU 的保持性(掉电保持)位存储器、定时器和计数器范围。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: RetentiveInfo 结构体
+    ///  - Err: 操作失败，或 CPU 不提供系统区域信息
+    ///
+    /// `注：数据块的保持性是各 DB 的块属性，不包含在结果中。`
+    ///
+    pub fn retentive_info(&self) -> Result<RetentiveInfo> {
+        let (data, record_len, count) = self.read_szl_records(0x0014, 0)?;
+        RetentiveInfo::from_szl(&data, record_len, count).ok_or_else(|| {
+            anyhow!("the CPU does not provide retentive area information (SZL 0x0014)")
+        })
+    }
+
+    ///
+    /// 读取诊断缓冲区(SZL 0x00A0)并解码其中带时间戳的 CPU 模式切换记录。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: (切换时间, 切换后的状态) 列表，与诊断缓冲区一致，最新的记录在前
+    ///  - Err: 操作失败，或 CPU 不提供诊断缓冲区
+    ///
+    /// `注：只包含进入 RUN 或 STOP 的切换，STARTUP、HOLD 等中间状态以及时间戳无法解码的记录被忽略。`
+    ///
+    pub fn status_history(&self) -> Result<Vec<(chrono::DateTime<chrono::Utc>, CpuStatus)>> {
+        let (data, record_len, count) = self.read_szl_records(0x00A0, 0)?;
+        // 每条诊断记录: 事件 ID(2 字节)、附加信息(10 字节)、BCD 时间戳(8 字节)
+        if record_len < 20 {
+            bail!("unexpected diagnostic buffer record length {}", record_len);
+        }
+        Ok(data
+            .chunks_exact(record_len)
+            .take(count)
+            .filter_map(|record| {
+                let status = CpuStatus::from_diag_event(getters::get_word(record, 0))?;
+                let time = getters::try_get_date_time_object(record, 12).ok()?;
+                Some((time, status))
+            })
+            .collect())
+    }
+
+    ///
+    /// 扫描子网内可连接的 PLC，并读取其组件标识。
+    ///
+    /// 并发探测(最多 32 个主机同时进行)，每个主机的连接和应答等待都受 timeout 限制，
+    /// 总耗时约为 ceil(主机数 / 32) * 2 * timeout。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - base: 子网内任意地址
+    ///  - mask: 子网掩码，如 255.255.255.0，必须连续且不短于 /16
+    ///  - rack: 机架号
+    ///  - slot: 插槽号
+    ///  - timeout: 单个主机的连接超时和应答超时
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 按地址排序的 (地址, 组件标识) 列表；能连接但不支持 SZL 0x001C 的设备返回默认(空)标识
+    ///  - Err: 掩码不连续或子网大于 /16
+    ///
+    /// # Examples
+    /// ```ignore
+    /// use std::{net::Ipv4Addr, time::Duration};
+    ///
+    /// let found = S7Client::scan_subnet(
+    ///     Ipv4Addr::new(192, 168, 0, 0),
+    ///     Ipv4Addr::new(255, 255, 255, 0),
+    ///     0,
+    ///     1,
+    ///     Duration::from_millis(500),
+    /// )?;
+    /// for (address, identity) in found {
+    ///     println!("{}: {}", address, identity.module_type_name);
+    /// }
+    /// ```
+    pub fn scan_subnet(
+        base: Ipv4Addr,
+        mask: Ipv4Addr,
+        rack: i32,
+        slot: i32,
+        timeout: Duration,
+    ) -> Result<Vec<(Ipv4Addr, ComponentIdentification)>> {
+        let hosts = Self::subnet_hosts(base, mask)?;
+        Ok(Self::scan_hosts(&hosts, 102, rack, slot, timeout))
     }
 
     ///
@@ -1467,14 +2993,15 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    ///  `注：该功能受制于设定的安全级别。`
+    ///  `注：该功能受制于设定的安全级别。如果 CPU 已处于 RUN 模式，返回 Snap7Error::CpuAlreadyRunning。`
     ///
     pub fn plc_hot_start(&self) -> Result<()> {
+        self.ensure_cpu_not_running()?;
         let res = unsafe { Cli_PlcHotStart(self.handle) };
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1485,14 +3012,15 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    ///  `注：该功能受制于设定的安全级别。`
+    ///  `注：该功能受制于设定的安全级别。如果 CPU 已处于 RUN 模式，返回 Snap7Error::CpuAlreadyRunning。`
     ///
     pub fn plc_cold_start(&self) -> Result<()> {
+        self.ensure_cpu_not_running()?;
         let res = unsafe { Cli_PlcColdStart(self.handle) };
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1510,7 +3038,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1525,14 +3053,15 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    ///  `注：不是所有的 CPU 都支持这个操作，CPU 必须处于 STOP 模式。`
+    ///  `注：不是所有的 CPU 都支持这个操作，CPU 必须处于 STOP 模式，否则返回 Snap7Error::CpuNotStopped。`
     ///
     pub fn copy_ram_to_rom(&self, timeout: i32) -> Result<()> {
+        self.ensure_cpu_stopped()?;
         let res = unsafe { Cli_CopyRamToRom(self.handle, timeout) };
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1547,14 +3076,15 @@ impl S7Client {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    ///  `注：不是所有的 CPU 都支持这个操作，CPU 必须处于 STOP 模式。`
+    ///  `注：不是所有的 CPU 都支持这个操作，CPU 必须处于 STOP 模式，否则返回 Snap7Error::CpuNotStopped。`
     ///
     pub fn compress(&self, timeout: i32) -> Result<()> {
+        self.ensure_cpu_stopped()?;
         let res = unsafe { Cli_Compress(self.handle, timeout) };
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1577,7 +3107,42 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 获取 PLC 状态，如果因连接错误失败，则重新连接并重试一次。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: PLC 状态
+    ///  - Err: 非连接错误、重新连接失败或重试仍失败
+    ///
+    pub fn plc_status_or_reconnect(&self) -> Result<CpuStatus> {
+        let mut status = S7_CPU_STATUS_UNKNOWN;
+        let res = unsafe { Cli_GetPlcStatus(self.handle, &mut status as *mut c_int) };
+        if res == 0 {
+            return Ok(CpuStatus::from_raw(status));
+        }
+        if !Self::is_connection_error(res) {
+            bail!(Snap7Error::client(res))
+        }
+        self.reconnect()?;
+        self.get_plc_status(&mut status)?;
+        Ok(CpuStatus::from_raw(status))
+    }
+
+    ///
+    /// 断开并使用之前的连接参数重新连接到 PLC。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn reconnect(&self) -> Result<()> {
+        self.disconnect()?;
+        self.connect()
     }
 
     ///
@@ -1598,7 +3163,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1614,7 +3179,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1634,7 +3199,27 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 获取 CPU 保护状态，解析出读/写是否需要密码以及当前会话是否已通过密码验证。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: ProtectionState 结构体
+    ///  - Err: 操作失败
+    ///
+    pub fn protection_state(&self) -> Result<ProtectionState> {
+        let mut protection = TS7Protection {
+            sch_schal: 0,
+            sch_par: 0,
+            sch_rel: 0,
+            bart_sch: 0,
+            anl_sch: 0,
+        };
+        self.get_protection(&mut protection)?;
+        Ok(ProtectionState::from(protection))
     }
 
     ///
@@ -1661,7 +3246,28 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 与 CPU 交换一个 S7 请求 PDU，并解析应答的功能码和错误字段。
+    ///
+    /// **输入参数**
+    ///
+    ///  - pdu: 请求 PDU(以协议号 0x32 开头)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 解析后的应答，报文头中的错误通过 S7Response::is_ok() 判断
+    ///  - Err: 交换失败或应答不是有效的 S7 PDU
+    ///
+    pub fn iso_exchange(&self, pdu: &[u8]) -> Result<S7Response> {
+        // 应答不会超过 snap7 的 ISO 负载上限
+        let mut buff = vec![0u8; pdu.len().max(4096)];
+        buff[..pdu.len()].copy_from_slice(pdu);
+        let mut size = pdu.len() as i32;
+        self.iso_exchange_buffer(&mut buff, &mut size)?;
+        S7Response::parse(&buff[..(size.max(0) as usize).min(buff.len())]).map_err(Error::msg)
     }
 
     ///
@@ -1681,7 +3287,7 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1701,7 +3307,7 @@ impl S7Client {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::client(res))
         }
     }
 
@@ -1727,7 +3333,7 @@ impl S7Client {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::client(res))
         }
     }
 
@@ -1765,7 +3371,23 @@ impl S7Client {
         if res == 0 {
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 检查连接状态，未连接时使用先前的连接参数调用 connect()。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 已连接
+    ///  - Err: 重新连接失败
+    ///
+    pub fn ensure_connected(&self) -> Result<()> {
+        let mut is_connected = 0;
+        if self.get_connected(&mut is_connected).is_err() || is_connected == 0 {
+            self.connect()?;
+        }
+        Ok(())
     }
 
     ///
@@ -1782,7 +3404,7 @@ impl S7Client {
     /// # Examples
     /// ```ignore
     /// client.set_as_callback(Some(|_, op_code, op_result| {
-    ///     println!("op_code: {}", op_code);
+    ///     println!("op: {:?}", AsyncOp::from_code(op_code));
     ///     println!("op_result: {:?}", S7Client::error_text(op_result));
     /// })).unwrap();
     /// ```
@@ -1790,27 +3412,59 @@ impl S7Client {
     where
         F: FnMut(*mut c_void, c_int, c_int) + 'static,
     {
-        if callback.is_some() {
+        if let Some(callback) = callback {
             unsafe {
-                let data = Box::into_raw(Box::new(callback));
-                let res =
-                    Cli_SetAsCallback(self.handle, Some(call_as_closure::<F>), data as *mut c_void);
+                let data = CallbackBox::new(callback);
+                let res = Cli_SetAsCallback(
+                    self.handle,
+                    Some(call_as_closure::<F>),
+                    data.ptr as *mut c_void,
+                );
                 if res == 0 {
+                    self.replace_as_callback(Some(data));
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::client(res))
             }
         } else {
             unsafe {
                 let res = Cli_SetAsCallback(self.handle, None, std::ptr::null_mut() as *mut c_void);
                 if res == 0 {
+                    self.replace_as_callback(None);
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::client(res))
             }
         }
     }
 
+    ///
+    /// 确定地关闭客户端：断开连接、清除并释放异步回调、销毁 snap7 客户端对象。
+    ///
+    /// 该函数会消耗客户端，关闭后无法再使用:
+    ///
+    /// ```compile_fail
+    /// use rust_snap7::S7Client;
+    ///
+    /// let client = S7Client::create();
+    /// client.close().unwrap();
+    /// client.connect().unwrap();
+    /// ```
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 断开连接或清除回调失败，客户端对象仍会被销毁
+    ///
+    pub fn close(mut self) -> Result<()> {
+        let disconnected = self.disconnect();
+        let cleared = self.set_as_callback(None::<fn(_, _, _)>);
+        unsafe {
+            Cli_Destroy(&mut self.handle as *mut S7Object);
+        }
+        disconnected.and(cleared)
+    }
+
     ///
     /// 检查当前的异步任务是否完成并立即返回。
     ///
@@ -1859,6 +3513,71 @@ impl S7Client {
         unsafe { Cli_WaitAsCompletion(self.handle, timeout) }
     }
 
+    ///
+    /// 检查是否有异步任务正在进行。
+    ///
+    /// 有异步任务进行时再发起新的异步调用会返回 errCliJobPending，可先用该函数判断。
+    ///
+    /// **返回值:**
+    ///
+    ///  - true: 有异步任务正在进行
+    ///  - false: 没有进行中的异步任务
+    ///
+    pub fn is_busy(&self) -> bool {
+        let mut op_result = 0;
+        self.check_as_completion(&mut op_result) == JobPending
+    }
+
+    ///
+    /// 返回正在进行的异步任务的 snap7 操作码，便于调试卡住的任务。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 操作码(如 1 读区域、2 写区域、6 上传、7 下载)
+    ///  - None: 没有进行中的异步任务
+    ///
+    pub fn pending_op_code(&self) -> Option<i32> {
+        self.is_busy()
+            .then(|| self.async_op.load(Ordering::Relaxed))
+    }
+
+    ///
+    /// 返回正在进行的异步任务的操作类型。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 操作类型
+    ///  - None: 没有进行中的异步任务
+    ///
+    pub fn pending_op(&self) -> Option<AsyncOp> {
+        self.pending_op_code().and_then(AsyncOp::from_code)
+    }
+
+    ///
+    /// 等待当前正在进行的异步任务结束。
+    ///
+    /// snap7 无法取消已发出的异步任务，因此该函数会一直等待到任务完成(或因通讯超时而失败)，
+    /// 任务本身的执行结果将被忽略。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 没有正在进行的异步任务
+    ///  - Err: 操作失败
+    ///
+    pub fn abort_async(&self) -> Result<()> {
+        let mut op_result = 0;
+        loop {
+            let res = self.check_as_completion(&mut op_result);
+            if res == JobComplete {
+                return Ok(());
+            }
+            if res != JobPending {
+                bail!(Snap7Error::client(res))
+            }
+            self.wait_as_completion(100);
+        }
+    }
+
     ///
     /// 从 PLC 中异步读取数据, 你可以读取数据块(DB)、输入、输出、内部标志位(Merkers)、定时器和计数器。
     ///
@@ -1901,9 +3620,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ReadArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1948,9 +3669,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::WriteArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -1987,9 +3710,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ReadArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2032,9 +3757,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::WriteArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2069,9 +3796,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ReadArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2106,9 +3835,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::WriteArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2143,9 +3874,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ReadArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2180,9 +3913,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::WriteArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2217,9 +3952,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ReadArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2254,9 +3991,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::WriteArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2292,9 +4031,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ReadArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2330,9 +4071,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::WriteArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2368,9 +4111,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ReadArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2406,9 +4151,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::WriteArea as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2447,9 +4194,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ListBlocksOfType as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2484,9 +4233,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ReadSzl as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2515,9 +4266,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::ReadSzlList as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2559,9 +4312,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::Upload as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2603,9 +4358,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::Upload as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2634,9 +4391,11 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::Download as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
     }
 
     ///
@@ -2664,91 +4423,918 @@ impl S7Client {
             )
         };
         if res == 0 {
+            self.async_op
+                .store(AsyncOp::DbGet as i32, Ordering::Relaxed);
+            return Ok(());
+        }
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 用一个给定的字节异步填充 AG 中的一个 DB，而不需要指定其大小。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - block_num: 要填充的 DB 块编号
+    ///  - fill_char: 要填充的字节
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    ///  `注：出于效率考虑，fill_char 是一个整数，且只有最低的字节被使用`
+    ///
+    pub fn as_db_fill(&self, block_num: i32, fill_char: i32) -> Result<()> {
+        let res = unsafe { Cli_AsDBFill(self.handle, block_num as c_int, fill_char as c_int) };
+        if res == 0 {
+            self.async_op
+                .store(AsyncOp::DbFill as i32, Ordering::Relaxed);
+            return Ok(());
+        }
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 异步执行复制 RAM 到 ROM。
+    ///
+    /// **输入参数:**
+    ///
+    /// - timeout: 预期完成操作的最大时间(ms)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    ///  `注：不是所有的 CPU 都支持这个操作，CPU 必须处于 STOP 模式，否则返回 Snap7Error::CpuNotStopped。`
+    ///
+    pub fn as_copy_ram_to_rom(&self, timeout: i32) -> Result<()> {
+        self.ensure_cpu_stopped()?;
+        let res = unsafe { Cli_AsCopyRamToRom(self.handle, timeout) };
+        if res == 0 {
+            self.async_op
+                .store(AsyncOp::CopyRamToRom as i32, Ordering::Relaxed);
             return Ok(());
         }
-        bail!("{}", Self::error_text(res))
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 异步执行内存压缩。
+    ///
+    /// **输入参数:**
+    ///
+    /// - timeout: 预期完成操作的最大时间(ms)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    ///  `注：不是所有的 CPU 都支持这个操作，CPU 必须处于 STOP 模式，否则返回 Snap7Error::CpuNotStopped。`
+    ///
+    pub fn as_compress(&self, timeout: i32) -> Result<()> {
+        self.ensure_cpu_stopped()?;
+        let res = unsafe { Cli_AsCompress(self.handle, timeout) };
+        if res == 0 {
+            self.async_op
+                .store(AsyncOp::Compress as i32, Ordering::Relaxed);
+            return Ok(());
+        }
+        bail!(Snap7Error::client(res))
+    }
+
+    ///
+    /// 异步执行内存压缩并等待其完成。
+    ///
+    /// **输入参数:**
+    ///
+    /// - timeout: 预期完成操作的最大时间(ms)，同时作为等待的超时时间
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 压缩完成
+    ///  - Err: 操作失败或等待超时(超时后任务仍在后台进行，可用 abort_async 等待其结束)
+    ///
+    ///  `注：不是所有的 CPU 都支持这个操作，CPU 必须处于 STOP 模式，否则返回 Snap7Error::CpuNotStopped。`
+    ///
+    pub fn compress_wait(&self, timeout: i32) -> Result<()> {
+        self.as_compress(timeout)?;
+        let res = self.wait_as_completion(timeout);
+        if res == 0 {
+            return Ok(());
+        }
+        bail!(Snap7Error::client(res))
+    }
+
+    /// 写入一个位并读回校验，读回值不符时返回 Snap7Error::BitMismatch。
+    fn write_bit_verified(
+        &self,
+        area: AreaTable,
+        byte_index: i32,
+        bit_index: i32,
+        value: bool,
+    ) -> Result<()> {
+        self.write_area_bit(area, 0, byte_index, bit_index, value)?;
+        let actual = self.read_area_bit(area, 0, byte_index, bit_index)?;
+        if actual != value {
+            bail!(Snap7Error::BitMismatch {
+                expected: value,
+                actual
+            });
+        }
+        Ok(())
+    }
+
+    /// 子网内的主机地址，掩码短于 /31 时不含网络地址和广播地址。
+    fn subnet_hosts(base: Ipv4Addr, mask: Ipv4Addr) -> Result<Vec<Ipv4Addr>> {
+        let bits = u32::from(mask);
+        let prefix = bits.leading_ones();
+        if bits.checked_shl(prefix).unwrap_or(0) != 0 {
+            bail!("subnet mask {} is not contiguous", mask);
+        }
+        if prefix < SCAN_MIN_PREFIX {
+            bail!(
+                "subnet /{} is too large to scan, the shortest prefix is /{}",
+                prefix,
+                SCAN_MIN_PREFIX
+            );
+        }
+        let network = u32::from(base) & bits;
+        let broadcast = network | !bits;
+        if broadcast - network < 2 {
+            Ok((network..=broadcast).map(Ipv4Addr::from).collect())
+        } else {
+            Ok((network + 1..broadcast).map(Ipv4Addr::from).collect())
+        }
+    }
+
+    fn scan_hosts(
+        hosts: &[Ipv4Addr],
+        port: u16,
+        rack: i32,
+        slot: i32,
+        timeout: Duration,
+    ) -> Vec<(Ipv4Addr, ComponentIdentification)> {
+        let next = AtomicUsize::new(0);
+        let found = Mutex::new(Vec::new());
+        std::thread::scope(|s| {
+            for _ in 0..SCAN_CONCURRENCY.min(hosts.len()) {
+                s.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    if i >= hosts.len() {
+                        break;
+                    }
+                    if let Some(identity) = Self::probe(hosts[i], port, rack, slot, timeout) {
+                        found
+                            .lock()
+                            .unwrap_or_else(|e| e.into_inner())
+                            .push((hosts[i], identity));
+                    }
+                });
+            }
+        });
+        let mut found = found.into_inner().unwrap_or_else(|e| e.into_inner());
+        found.sort_by_key(|(address, _)| *address);
+        found
+    }
+
+    /// 尝试连接一个主机，连接失败返回 None。
+    fn probe(
+        address: Ipv4Addr,
+        port: u16,
+        rack: i32,
+        slot: i32,
+        timeout: Duration,
+    ) -> Option<ComponentIdentification> {
+        let timeout = timeout.as_millis().min(i32::MAX as u128) as i32;
+        let client = S7Client::create();
+        client
+            .set_param(InternalParam::RemotePort, InternalParamValue::U16(port))
+            .ok()?;
+        client
+            .set_param(InternalParam::PingTimeout, InternalParamValue::I32(timeout))
+            .ok()?;
+        client
+            .set_param(InternalParam::RecvTimeout, InternalParamValue::I32(timeout))
+            .ok()?;
+        client.connect_to(&address.to_string(), rack, slot).ok()?;
+        let identity = client.component_identification().unwrap_or_default();
+        let _ = client.disconnect();
+        Some(identity)
+    }
+
+    /// 用一次 read_multi_vars() 回读 group 中的标签并与写入数据比较。
+    fn verify_group(&self, writes: &[TagWrite], group: &[usize], results: &mut [Result<()>]) {
+        let mut buffers: Vec<Vec<u8>> = group
+            .iter()
+            .map(|i| vec![0u8; writes[*i].data.len()])
+            .collect();
+        let mut items: Vec<TS7DataItem> = group
+            .iter()
+            .zip(buffers.iter_mut())
+            .map(|(i, buff)| TS7DataItem {
+                Area: writes[*i].area as c_int,
+                WordLen: WordLenTable::S7WLByte as c_int,
+                Result: 0,
+                DBNumber: writes[*i].db_number,
+                Start: writes[*i].start,
+                Amount: buff.len() as c_int,
+                pdata: buff.as_mut_ptr() as *mut c_void,
+            })
+            .collect();
+        let items_count = items.len() as i32;
+        if let Err(e) = self.read_multi_vars(&mut items, items_count) {
+            let message = e.to_string();
+            for i in group {
+                results[*i] = Err(anyhow!("{}", message));
+            }
+            return;
+        }
+        for ((i, item), actual) in group.iter().zip(items.iter()).zip(buffers) {
+            results[*i] = if item.Result != 0 {
+                Err(Snap7Error::client(item.Result).into())
+            } else {
+                Self::compare_read_back(&writes[*i].data, actual)
+            };
+        }
+    }
+
+    fn compare_read_back(expected: &[u8], actual: Vec<u8>) -> Result<()> {
+        if expected != actual.as_slice() {
+            bail!(Snap7Error::DataMismatch {
+                expected: expected.to_vec(),
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    /// 替换保存的异步回调，旧回调在此释放。
+    fn replace_as_callback(&self, callback: Option<CallbackBox>) {
+        let mut slot = self.as_callback.lock().unwrap_or_else(|e| e.into_inner());
+        *slot = callback;
+    }
+
+    /// 错误代码中的 TCP(低 16 位)或 ISO 部分不为 0 时，表示连接层错误。
+    fn is_connection_error(code: i32) -> bool {
+        code & 0x000F_FFFF != 0
+    }
+
+    /// 读取一个 SZL 部分列表，返回 (不含 SZL 头的数据, 记录长度, 记录数)。
+    fn read_szl_records(&self, id: i32, index: i32) -> Result<(Vec<u8>, usize, usize)> {
+        let mut szl = Box::new(TS7SZL {
+            Header: SZL_HEADER {
+                LENTHDR: 0,
+                N_DR: 0,
+            },
+            Data: [0; 16380],
+        });
+        let mut size = std::mem::size_of::<TS7SZL>() as i32;
+        self.read_szl(id, index, &mut szl, &mut size)?;
+        let data_len = (size as usize).saturating_sub(std::mem::size_of::<SZL_HEADER>());
+        Ok((
+            szl.Data[..data_len.min(szl.Data.len())].to_vec(),
+            szl.Header.LENTHDR as usize,
+            szl.Header.N_DR as usize,
+        ))
+    }
+
+    /// 错误是否为 CPU 不提供所请求的项目(errCliItemNotAvailable)。
+    fn is_item_not_available(error: &Error) -> bool {
+        error
+            .downcast_ref::<Snap7Error>()
+            .and_then(Snap7Error::code)
+            .is_some_and(|code| code as u32 == errCliItemNotAvailable)
+    }
+
+    /// 单个写请求可携带的最大数据字节数(PDU 长度减去报文头)。
+    fn max_write_chunk(&self) -> Result<usize> {
+        let (mut requested, mut negotiated) = (0, 0);
+        self.get_pdu_length(&mut requested, &mut negotiated)?;
+        let pdu = if negotiated > 0 { negotiated } else { 240 };
+        Ok((pdu - 35).max(1) as usize)
+    }
+
+    /// 检查 CPU 不处于 RUN 模式，状态未知时交由 PLC 判断。
+    fn ensure_cpu_stopped(&self) -> Result<()> {
+        let mut status = S7_CPU_STATUS_UNKNOWN;
+        self.get_plc_status(&mut status)?;
+        if status == S7_CPU_STATUS_RUN {
+            bail!(Snap7Error::CpuNotStopped)
+        }
+        Ok(())
+    }
+
+    /// 检查 CPU 尚未处于 RUN 模式。
+    fn ensure_cpu_not_running(&self) -> Result<()> {
+        let mut status = S7_CPU_STATUS_UNKNOWN;
+        self.get_plc_status(&mut status)?;
+        if status == S7_CPU_STATUS_RUN {
+            bail!(Snap7Error::CpuAlreadyRunning)
+        }
+        Ok(())
+    }
+}
+
+unsafe extern "C" fn call_as_closure<F>(usr_ptr: *mut c_void, op_code: c_int, op_result: c_int)
+where
+    F: FnMut(*mut c_void, c_int, c_int),
+{
+    let callback_ptr = usr_ptr as *mut F;
+    let callback = &mut *callback_ptr;
+    callback(usr_ptr, op_code, op_result);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{server::S7Server, test_support::*};
+
+    #[test]
+    fn test_cpu_state_preconditions() {
+        let mut db_buff = [0u8; 64];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let err = client.compress(1000).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Snap7Error>(),
+            Some(&Snap7Error::CpuNotStopped)
+        );
+        let err = client.copy_ram_to_rom(1000).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Snap7Error>(),
+            Some(&Snap7Error::CpuNotStopped)
+        );
+        let err = client.plc_hot_start().unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Snap7Error>(),
+            Some(&Snap7Error::CpuAlreadyRunning)
+        );
+
+        client.plc_stop().unwrap();
+        assert!(client.compress(1000).is_ok());
+        assert!(client.plc_cold_start().is_ok());
+    }
+
+    #[test]
+    fn test_db_read_byte_and_bit() {
+        let mut db_buff = [0u8; 64];
+        db_buff[3] = 0b0010_0100;
+        let (_server, client) = connect_local(&mut db_buff);
+
+        assert_eq!(
+            client.db_read_byte_and_bit(1, 3, 2).unwrap(),
+            (0b0010_0100, true)
+        );
+        assert_eq!(
+            client.db_read_byte_and_bit(1, 3, 3).unwrap(),
+            (0b0010_0100, false)
+        );
+        assert!(client.db_read_byte_and_bit(1, 3, 8).is_err());
+        assert!(client.db_read_byte_and_bit(1, 3, -1).is_err());
+    }
+
+    #[test]
+    fn test_read_process_image() {
+        let mut pe_buff = [0u8; 512];
+        let mut pa_buff = [0u8; 512];
+        for (i, b) in pe_buff.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        for (i, b) in pa_buff.iter_mut().enumerate() {
+            *b = !(i as u8);
+        }
+        let (_server, port) = start_server(&mut [
+            (AreaCode::S7AreaPE, 0, &mut pe_buff),
+            (AreaCode::S7AreaPA, 0, &mut pa_buff),
+        ]);
+        let client = connect_client(port);
+
+        // 一个 PDU 内的单次请求
+        let (inputs, outputs) = client.read_process_image(16, 8).unwrap();
+        assert_eq!(inputs, pe_buff[..16]);
+        assert_eq!(outputs, pa_buff[..8]);
+
+        // 超出 PDU 时分别读取
+        let (inputs, outputs) = client.read_process_image(512, 512).unwrap();
+        assert_eq!(inputs, pe_buff);
+        assert_eq!(outputs, pa_buff);
+    }
+
+    #[test]
+    fn test_db_set_bit() {
+        let mut db_buff = [0u8; 64];
+        db_buff[5] = 0b1010_0001;
+        let (_server, client) = connect_local(&mut db_buff);
+
+        client.db_set_bit(1, 5, 3, true).unwrap();
+        assert_eq!(
+            client.db_read_byte_and_bit(1, 5, 3).unwrap(),
+            (0b1010_1001, true)
+        );
+        client.db_set_bit(1, 5, 3, false).unwrap();
+        client.db_set_bit(1, 5, 7, false).unwrap();
+        assert_eq!(
+            client.db_read_byte_and_bit(1, 5, 7).unwrap(),
+            (0b0010_0001, false)
+        );
+        assert!(client.db_set_bit(1, 5, 8, true).is_err());
+        assert!(client.db_set_bit(1, 5, -1, true).is_err());
+    }
+
+    #[test]
+    fn test_scan_subnet() {
+        assert_eq!(
+            S7Client::subnet_hosts(
+                Ipv4Addr::new(10, 0, 0, 77),
+                Ipv4Addr::new(255, 255, 255, 252)
+            )
+            .unwrap(),
+            vec![Ipv4Addr::new(10, 0, 0, 77), Ipv4Addr::new(10, 0, 0, 78)]
+        );
+        assert_eq!(
+            S7Client::subnet_hosts(
+                Ipv4Addr::new(10, 0, 0, 77),
+                Ipv4Addr::new(255, 255, 255, 255)
+            )
+            .unwrap(),
+            vec![Ipv4Addr::new(10, 0, 0, 77)]
+        );
+        assert_eq!(
+            S7Client::subnet_hosts(Ipv4Addr::new(10, 0, 1, 9), Ipv4Addr::new(255, 255, 255, 0))
+                .unwrap()
+                .len(),
+            254
+        );
+        assert_eq!(
+            S7Client::subnet_hosts(Ipv4Addr::new(10, 0, 1, 9), Ipv4Addr::new(255, 255, 0, 0))
+                .unwrap()
+                .len(),
+            65534
+        );
+        assert!(
+            S7Client::subnet_hosts(Ipv4Addr::new(10, 0, 1, 9), Ipv4Addr::new(255, 0, 0, 0))
+                .is_err()
+        );
+        assert!(S7Client::subnet_hosts(Ipv4Addr::new(10, 0, 1, 9), Ipv4Addr::UNSPECIFIED).is_err());
+        assert!(S7Client::subnet_hosts(
+            Ipv4Addr::new(10, 0, 1, 9),
+            Ipv4Addr::new(255, 255, 0, 255)
+        )
+        .is_err());
+
+        // 服务端只绑定 127.0.0.1，127.0.0.2 应被忽略
+        let port = next_test_port();
+        let server = S7Server::create();
+        server
+            .set_param(InternalParam::LocalPort, InternalParamValue::U16(port))
+            .unwrap();
+        server.start_to("127.0.0.1").unwrap();
+        let hosts = S7Client::subnet_hosts(
+            Ipv4Addr::new(127, 0, 0, 1),
+            Ipv4Addr::new(255, 255, 255, 252),
+        )
+        .unwrap();
+        let found = S7Client::scan_hosts(&hosts, port, 0, 1, Duration::from_millis(500));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, Ipv4Addr::new(127, 0, 0, 1));
+        assert_eq!(found[0].1.module_type_name, "CPU 315-2 PN/DP");
+    }
+
+    #[test]
+    fn test_db_time_round_trip() {
+        let mut db_buff = [0u8; 64];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let value = chrono::Duration::milliseconds(93_784_005);
+        client.db_write_time(1, 8, value).unwrap();
+        assert_eq!(client.db_read_time(1, 8).unwrap(), value);
+
+        let value = chrono::Duration::milliseconds(-1_500);
+        client.db_write_time(1, 12, value).unwrap();
+        assert_eq!(client.db_read_time(1, 12).unwrap(), value);
+
+        assert!(client
+            .db_write_time(1, 16, chrono::Duration::days(25))
+            .is_err());
+    }
+
+    #[test]
+    fn test_db_read_array() {
+        let mut db_buff = [0u8; 64];
+        db_buff[20..24].copy_from_slice(&13.14f32.to_be_bytes());
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let buff = client.db_read_array::<4>(1, 20).unwrap();
+        assert_eq!(getters::get_real(&buff, 0), 13.14);
+    }
+
+    #[test]
+    fn test_read_timers_and_counters() {
+        let mut db_buff = [0u8; 64];
+        let mut tm_buff = [0u8; 16];
+        let mut ct_buff = [0u8; 16];
+        tm_buff[..4].copy_from_slice(&[0x12, 0x34, 0x21, 0x50]);
+        ct_buff[..4].copy_from_slice(&[0x01, 0x23, 0x09, 0x99]);
+        let (server, client) = connect_local(&mut db_buff);
+        server
+            .register_area(AreaCode::S7AreaTM, 0, &mut tm_buff)
+            .unwrap();
+        server
+            .register_area(AreaCode::S7AreaCT, 0, &mut ct_buff)
+            .unwrap();
+
+        let timers = client.read_timers(0, 2).unwrap();
+        assert_eq!(
+            timers,
+            vec![
+                std::time::Duration::from_millis(23400),
+                std::time::Duration::from_secs(150)
+            ]
+        );
+        assert_eq!(client.read_counters(0, 2).unwrap(), vec![123, 999]);
+    }
+
+    #[test]
+    fn test_area_bit() {
+        let mut db_buff = [0u8; 8];
+        let mut pa_buff = [0u8; 4];
+        let mut mk_buff = [0u8; 4];
+        let (server, client) = connect_local(&mut db_buff);
+        server
+            .register_area(AreaCode::S7AreaPA, 0, &mut pa_buff)
+            .unwrap();
+        server
+            .register_area(AreaCode::S7AreaMK, 0, &mut mk_buff)
+            .unwrap();
+
+        for (area, db_number) in [
+            (AreaTable::S7AreaDB, 1),
+            (AreaTable::S7AreaMK, 0),
+            (AreaTable::S7AreaPA, 0),
+        ] {
+            assert!(!client.read_area_bit(area, db_number, 2, 5).unwrap());
+            client.write_area_bit(area, db_number, 2, 5, true).unwrap();
+            assert!(client.read_area_bit(area, db_number, 2, 5).unwrap());
+            assert!(!client.read_area_bit(area, db_number, 2, 4).unwrap());
+            assert!(client.write_area_bit(area, db_number, 2, 8, true).is_err());
+        }
+        client
+            .write_area_bit(AreaTable::S7AreaMK, 0, 2, 5, false)
+            .unwrap();
+
+        drop(client);
+        drop(server);
+        assert_eq!(db_buff[2], 0b0010_0000);
+        assert_eq!(pa_buff[2], 0b0010_0000);
+        assert_eq!(mk_buff[2], 0);
+    }
+
+    #[test]
+    fn test_write_bit_verified() {
+        let mut db_buff = [0u8; 64];
+        let mut pa_buff = [0u8; 4];
+        let mut mk_buff = [0u8; 4];
+        let (server, client) = connect_local(&mut db_buff);
+        server
+            .register_area(AreaCode::S7AreaPA, 0, &mut pa_buff)
+            .unwrap();
+        server
+            .register_area(AreaCode::S7AreaMK, 0, &mut mk_buff)
+            .unwrap();
+
+        client.write_output_bit_verified(1, 3, true).unwrap();
+        client.write_merker_bit_verified(2, 7, true).unwrap();
+        client.write_merker_bit_verified(2, 7, false).unwrap();
+        assert!(client.write_output_bit_verified(0, 8, true).is_err());
+
+        drop(client);
+        drop(server);
+        assert_eq!(pa_buff[1], 0b0000_1000);
+        assert_eq!(mk_buff[2], 0);
+    }
+
+    #[test]
+    fn test_disconnect_flushes_async() {
+        let mut db_buff = [0u8; 64];
+        db_buff[..4].copy_from_slice(&[1, 2, 3, 4]);
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let mut buff = [0u8; 4];
+        client.as_db_read(1, 0, 4, &mut buff).unwrap();
+        client.disconnect().unwrap();
+
+        let mut op_result = -1;
+        assert_eq!(client.check_as_completion(&mut op_result), JobComplete);
+        assert_eq!(op_result, 0);
+        assert_eq!(buff, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_read_record() {
+        use crate::utils::layout::S7Type;
+
+        let mut db_buff = [0u8; 64];
+        db_buff[0..4].copy_from_slice(&3.5f32.to_be_bytes());
+        db_buff[4..6].copy_from_slice(&1200i16.to_be_bytes());
+        db_buff[6] = 0b1000_0000;
+        db_buff[8..14].copy_from_slice(&[10, 4, b'p', b'u', b'm', b'p']);
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let layout = Layout::new([
+            ("flow", S7Type::Real, 0),
+            ("rpm", S7Type::Int, 4),
+            ("running", S7Type::Bool(7), 6),
+            ("name", S7Type::String(10), 8),
+        ]);
+        let record = client.read_record(1, &layout).unwrap();
+        assert_eq!(record.len(), 4);
+        assert_eq!(record["flow"], Value::Real(3.5));
+        assert_eq!(record["rpm"], Value::Int(1200));
+        assert_eq!(record["running"], Value::Bool(true));
+        assert_eq!(record["name"], Value::String("pump".to_string()));
+    }
+
+    #[test]
+    fn test_upload_and_describe() {
+        let mut db_buff = [0u8; 64];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 服务端可以返回区块信息，但不支持上传，错误应原样返回
+        let mut info = TS7BlockInfo::default();
+        client
+            .get_ag_block_info(BlockType::BlockDB, 1, &mut info)
+            .unwrap();
+        assert!(client.upload_and_describe(BlockType::BlockDB, 1).is_err());
+        assert!(client.upload_and_describe(BlockType::BlockDB, 99).is_err());
+
+        // 失败后客户端仍可继续使用
+        let mut buff = [0u8; 2];
+        client.db_read(1, 0, 2, &mut buff).unwrap();
+    }
+
+    #[test]
+    fn test_plc_status_or_reconnect() {
+        let mut db_buff = [0u8; 64];
+        let (server, client) = connect_local(&mut db_buff);
+        assert_eq!(client.plc_status_or_reconnect().unwrap(), CpuStatus::Run);
+
+        // 服务端重启会断开所有客户端连接
+        server.stop().unwrap();
+        server.start().unwrap();
+
+        assert_eq!(client.plc_status_or_reconnect().unwrap(), CpuStatus::Run);
+        let mut connected = 0;
+        client.get_connected(&mut connected).unwrap();
+        assert_ne!(connected, 0);
+    }
+
+    #[test]
+    fn test_close() {
+        use std::sync::Arc;
+
+        let mut db_buff = [0u8; 64];
+        let (server, client) = connect_local(&mut db_buff);
+        let marker = Arc::new(());
+        let captured = marker.clone();
+        client
+            .set_as_callback(Some(move |_, _, _| {
+                let _ = &captured;
+            }))
+            .unwrap();
+        assert_eq!(Arc::strong_count(&marker), 2);
+
+        client.close().unwrap();
+        // 回调闭包已被释放
+        assert_eq!(Arc::strong_count(&marker), 1);
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(server.status().unwrap().clients, 0);
+    }
+
+    #[test]
+    fn test_component_identification() {
+        let mut db_buff = [0u8; 64];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let id = client.component_identification().unwrap();
+        assert_eq!(id.as_name, "SNAP7-SERVER");
+        assert_eq!(id.module_name, "CPU 315-2 PN/DP");
+        assert_eq!(id.module_type_name, "CPU 315-2 PN/DP");
+        assert_eq!(id.copyright, "Original Siemens Equipment");
+        assert_eq!(id.serial_number, "S C-C2UR28922012");
+        assert_eq!(id.memory_card_serial_number, "MMC 267FF11F");
+        assert_eq!(id.plant_designation, "");
+    }
+
+    #[test]
+    fn test_write_tags_verified() {
+        use std::sync::{Arc, Mutex};
+
+        let memory = Arc::new(Mutex::new([0u8; 32]));
+        let shared = memory.clone();
+        let (_server, port) = start_server_with(|server| {
+            server
+                .set_rw_area_callback(Some(
+                    move |_, sender, operation, p_tag: PS7Tag, p_data: *mut c_void| {
+                        let request = RwRequest::from_tag(sender, unsafe { &*p_tag });
+                        let start = request.start as usize;
+                        let size = request.byte_size();
+                        let mut memory = shared.lock().unwrap();
+                        if start + size > memory.len() {
+                            return;
+                        }
+                        let data =
+                            unsafe { std::slice::from_raw_parts_mut(p_data as *mut u8, size) };
+                        if operation == OPERATION_READ {
+                            data.copy_from_slice(&memory[start..start + size]);
+                        } else if start < 16 {
+                            // 模拟服务端忽略 16 字节之后的写入
+                            memory[start..start + size].copy_from_slice(data);
+                        }
+                    },
+                ))
+                .unwrap();
+        });
+        let client = connect_client(port);
+
+        let tag = |start: i32, data: &[u8]| TagWrite {
+            area: AreaTable::S7AreaDB,
+            db_number: 1,
+            start,
+            data: data.to_vec(),
+        };
+        let writes = [tag(0, &[1, 2, 3]), tag(20, &[7]), tag(4, &[9, 9])];
+        let results = client.write_tags_verified(&writes).unwrap();
+        assert_eq!(results.len(), 3);
+        assert!(results[0].is_ok());
+        assert!(results[2].is_ok());
+        assert_eq!(
+            results[1]
+                .as_ref()
+                .unwrap_err()
+                .downcast_ref::<Snap7Error>(),
+            Some(&Snap7Error::DataMismatch {
+                expected: vec![7],
+                actual: vec![0],
+            })
+        );
+        assert_eq!(memory.lock().unwrap()[..6], [1, 2, 3, 0, 9, 9]);
+    }
+
+    #[test]
+    fn test_write_tags_verified_small_pdu() {
+        let mut db_buff = [0u8; 32];
+        let (_server, port) = start_server(&mut [(AreaCode::S7AreaDB, 1, &mut db_buff)]);
+        let client = S7Client::create();
+        client
+            .set_param(InternalParam::RemotePort, InternalParamValue::U16(port))
+            .unwrap();
+        client.request_pdu_size(240).unwrap();
+        client.connect_to("127.0.0.1", 0, 1).unwrap();
+
+        // 20 项的读取请求为 10 + 2 + 20 * 12 = 252 字节，超出 240 字节的 PDU
+        let writes: Vec<TagWrite> = (0..20)
+            .map(|i| TagWrite {
+                area: AreaTable::S7AreaDB,
+                db_number: 1,
+                start: i,
+                data: vec![i as u8 + 1],
+            })
+            .collect();
+        let results = client.write_tags_verified(&writes).unwrap();
+        assert!(results.iter().all(|result| result.is_ok()));
+        assert_eq!(db_buff[19], 20);
+    }
+
+    #[test]
+    fn test_is_busy() {
+        let (_server, port) = start_server_with(|server| {
+            server
+                .set_rw_area_callback(Some(|_, _, _, _, _| {
+                    std::thread::sleep(std::time::Duration::from_millis(300));
+                }))
+                .unwrap();
+        });
+        let client = connect_client(port);
+
+        assert!(!client.is_busy());
+        let mut buff = [0u8; 4];
+        client.as_db_read(1, 0, 4, &mut buff).unwrap();
+        assert!(client.is_busy());
+        assert_eq!(client.wait_as_completion(3000), 0);
+        assert!(!client.is_busy());
+    }
+
+    #[test]
+    fn test_clock_drift() {
+        let mut db_buff = [0u8; 64];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        client.set_plc_system_date_time().unwrap();
+        let now = chrono::Local::now().naive_local();
+        let drift = client.clock_drift_from(now).unwrap();
+        assert!(drift.num_seconds().abs() <= 2, "drift: {}", drift);
+        // 本地服务端按本地时间返回时钟，与 UTC 的偏差即为本地时区偏移
+        let drift = client.clock_drift().unwrap();
+        let expected = client
+            .clock_drift_from(chrono::Utc::now().naive_utc())
+            .unwrap();
+        assert!(
+            (drift - expected).num_seconds().abs() <= 2,
+            "drift: {}",
+            drift
+        );
+        let drift = client
+            .clock_drift_from(now + chrono::Duration::hours(1))
+            .unwrap();
+        assert!(
+            (drift + chrono::Duration::hours(1)).num_seconds().abs() <= 2,
+            "drift: {}",
+            drift
+        );
     }
 
-    ///
-    /// 用一个给定的字节异步填充 AG 中的一个 DB，而不需要指定其大小。
-    ///
-    /// **输入参数:**
-    ///
-    ///  - block_num: 要填充的 DB 块编号
-    ///  - fill_char: 要填充的字节
-    ///
-    /// **返回值:**
-    ///
-    ///  - Ok: 操作成功
-    ///  - Err: 操作失败
-    ///
-    ///  `注：出于效率考虑，fill_char 是一个整数，且只有最低的字节被使用`
-    ///
-    pub fn as_db_fill(&self, block_num: i32, fill_char: i32) -> Result<()> {
-        let res = unsafe { Cli_AsDBFill(self.handle, block_num as c_int, fill_char as c_int) };
-        if res == 0 {
-            return Ok(());
+    #[test]
+    fn test_db_write_all() {
+        let mut db_buff = [0u8; 1024];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let mut data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        assert_eq!(client.db_write_all(1, 10, &mut data).unwrap(), data.len());
+        assert_eq!(&db_buff[10..1010], &data[..]);
+
+        let mut data = vec![0xAAu8; 1200];
+        let err = client.db_write_all(1, 0, &mut data).unwrap_err();
+        match err.downcast_ref::<Snap7Error>() {
+            Some(Snap7Error::PartialWrite { written, .. }) => {
+                assert!(*written > 0 && *written < 1024)
+            }
+            other => panic!("unexpected error: {:?}", other),
         }
-        bail!("{}", Self::error_text(res))
     }
 
-    ///
-    /// 异步执行复制 RAM 到 ROM。
-    ///
-    /// **输入参数:**
-    ///
-    /// - timeout: 预期完成操作的最大时间(ms)
-    ///
-    /// **返回值:**
-    ///
-    ///  - Ok: 操作成功
-    ///  - Err: 操作失败
-    ///
-    ///  `注：不是所有的 CPU 都支持这个操作，CPU 必须处于 STOP 模式。`
-    ///
-    pub fn as_copy_ram_to_rom(&self, timeout: i32) -> Result<()> {
-        let res = unsafe { Cli_AsCopyRamToRom(self.handle, timeout) };
-        if res == 0 {
-            return Ok(());
-        }
-        bail!("{}", Self::error_text(res))
+    #[test]
+    fn test_szl_catalog_described() {
+        let mut db_buff = [0u8; 64];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let catalog = client.szl_catalog_described().unwrap();
+        assert!(!catalog.is_empty());
+        assert!(catalog.contains(&(0x0011, "Module identification")));
+        assert!(catalog.contains(&(0x00A0, "Diagnostic buffer")));
     }
 
-    ///
-    /// 异步执行内存压缩。
-    ///
-    /// **输入参数:**
-    ///
-    /// - timeout: 预期完成操作的最大时间(ms)
-    ///
-    /// **返回值:**
-    ///
-    ///  - Ok: 操作成功
-    ///  - Err: 操作失败
-    ///
-    ///  `注：不是所有的 CPU 都支持这个操作，CPU 必须处于 STOP 模式。`
-    ///
-    pub fn as_compress(&self, timeout: i32) -> Result<()> {
-        let res = unsafe { Cli_AsCompress(self.handle, timeout) };
-        if res == 0 {
-            return Ok(());
-        }
-        bail!("{}", Self::error_text(res))
+    #[test]
+    fn test_protection_state() {
+        let mut db_buff = [0u8; 64];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 本地服务端不设保护
+        let state = client.protection_state().unwrap();
+        assert_eq!(state.level, 1);
+        assert!(!state.read_password_required);
+        assert!(!state.write_password_required);
+        assert!(!state.authenticated);
+
+        // 设置了读写保护且尚未验证密码的 CPU
+        let state = ProtectionState::from(TS7Protection {
+            sch_schal: 1,
+            sch_par: 3,
+            sch_rel: 3,
+            bart_sch: 1,
+            anl_sch: 0,
+        });
+        assert!(state.read_password_required);
+        assert!(state.write_password_required);
+        assert!(!state.authenticated);
+
+        // 已通过密码验证的会话
+        let state = ProtectionState::from(TS7Protection {
+            sch_schal: 1,
+            sch_par: 2,
+            sch_rel: 1,
+            bart_sch: 1,
+            anl_sch: 0,
+        });
+        assert!(!state.read_password_required);
+        assert!(!state.write_password_required);
+        assert!(state.authenticated);
     }
-}
 
-unsafe extern "C" fn call_as_closure<F>(usr_ptr: *mut c_void, op_code: c_int, op_result: c_int)
-where
-    F: FnMut(*mut c_void, c_int, c_int),
-{
-    let callback_ptr = usr_ptr as *mut F;
-    let callback = &mut *callback_ptr;
-    callback(usr_ptr, op_code, op_result);
-}
+    #[test]
+    fn test_cp_info() {
+        let mut db_buff = [0u8; 64];
+        let (_server, client) = connect_local(&mut db_buff);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let info = client.cp_info().unwrap();
+        assert!(info.max_pdu_length > 0);
+        assert!(info.max_connections > 0);
+        assert!(info.max_mpi_rate > 0);
+        assert!(info.max_bus_rate > 0);
+    }
 
     #[test]
     fn test_client() {
@@ -3021,4 +5607,585 @@ mod tests {
             f32::from_be_bytes([buff[0], buff[1], buff[2], buff[3]])
         );
     }
+
+    #[test]
+    fn test_low_latency_mode() {
+        let client = S7Client::create();
+        client.low_latency_mode().unwrap();
+
+        let read = |param| {
+            let mut value = InternalParamValue::I32(0);
+            client.get_param(param, &mut value).unwrap();
+            match value {
+                InternalParamValue::I32(v) => v,
+                _ => panic!("unexpected param value"),
+            }
+        };
+        assert_eq!(read(InternalParam::PingTimeout), 250);
+        assert_eq!(read(InternalParam::SendTimeout), 10);
+        assert_eq!(read(InternalParam::RecvTimeout), 500);
+        assert_eq!(read(InternalParam::WorkInterval), 1);
+
+        client.set_recv_timeout(Duration::from_secs(2)).unwrap();
+        assert_eq!(read(InternalParam::RecvTimeout), 2000);
+        assert!(client
+            .set_send_timeout(Duration::from_secs(u64::MAX))
+            .is_err());
+    }
+
+    #[test]
+    fn test_read_szl_all() {
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 本地服务端为 SZL 0x0117 提供 INDEX 0..=4 共 5 个部分列表
+        let lists = client.read_szl_all(0x0117).unwrap();
+        assert_eq!(lists.len(), 5);
+        assert!(lists.iter().all(|list| !list.is_empty()));
+
+        let mut szl = TS7SZL {
+            Header: SZL_HEADER {
+                LENTHDR: 0,
+                N_DR: 0,
+            },
+            Data: [0; 16380],
+        };
+        let mut size = std::mem::size_of::<TS7SZL>() as i32;
+        client.read_szl(0x0117, 2, &mut szl, &mut size).unwrap();
+        let data_len = size as usize - std::mem::size_of::<SZL_HEADER>();
+        assert_eq!(lists[2], szl.Data[..data_len].to_vec());
+
+        assert!(client.read_szl_all(0x0111).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_compress_wait() {
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let err = client.compress_wait(1000).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Snap7Error>(),
+            Some(&Snap7Error::CpuNotStopped)
+        );
+
+        // 本地服务端支持压缩操作，STOP 模式下应正常完成
+        client.plc_stop().unwrap();
+        client.compress_wait(1000).unwrap();
+        assert!(!client.is_busy());
+    }
+
+    #[test]
+    fn test_db_hexdump() {
+        let mut db_buff = [0u8; 32];
+        db_buff[16..21].copy_from_slice(b"Snap7");
+        let (_server, client) = connect_local(&mut db_buff);
+
+        assert_eq!(
+            client.db_hexdump(1, 14, 8).unwrap(),
+            "0000000e: 0000 536e 6170 3700                      ..Snap7.\n"
+        );
+        assert!(client.db_hexdump(1, -1, 8).is_err());
+    }
+
+    #[test]
+    fn test_read_tag_verbose() {
+        use crate::utils::layout::S7Type;
+
+        let mut db_buff = [0u8; 16];
+        db_buff[4..8].copy_from_slice(&12.5f32.to_be_bytes());
+        db_buff[9] = 0b0000_1000;
+        let (_server, client) = connect_local(&mut db_buff);
+
+        let addr = "DB1.DBD4"
+            .parse::<TagAddress>()
+            .unwrap()
+            .with_type(S7Type::Real);
+        let (raw, value) = client.read_tag_verbose(&addr).unwrap();
+        assert_eq!(raw, 12.5f32.to_be_bytes().to_vec());
+        assert_eq!(value, Value::Real(getters::get_real(&raw, 0)));
+        assert_eq!(value, Value::Real(12.5));
+
+        let addr = "DB1.DBX9.3".parse::<TagAddress>().unwrap();
+        let (raw, value) = client.read_tag_verbose(&addr).unwrap();
+        assert_eq!(raw, vec![0b0000_1000]);
+        assert_eq!(value, Value::Bool(true));
+    }
+
+    #[test]
+    fn test_memory_info() {
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 本地服务端模拟的 CPU 315-2 PN/DP
+        let info = client.memory_info().unwrap();
+        assert_eq!(
+            info,
+            MemoryInfo {
+                work_total: 393216,
+                work_free: 393216 - 38,
+                load_total: 524288,
+                load_free: 524288 - 3078,
+            }
+        );
+    }
+
+    #[test]
+    fn test_request_pdu_size() {
+        let (_server, port) = start_server(&mut []);
+
+        let client = S7Client::create();
+        client
+            .set_param(InternalParam::RemotePort, InternalParamValue::U16(port))
+            .unwrap();
+        assert!(client.request_pdu_size(120).is_err());
+        assert!(client.request_pdu_size(1920).is_err());
+
+        for size in [240, 960] {
+            client.request_pdu_size(size).unwrap();
+            client.connect_to("127.0.0.1", 0, 1).unwrap();
+            let (mut requested, mut negotiated) = (0, 0);
+            client
+                .get_pdu_length(&mut requested, &mut negotiated)
+                .unwrap();
+            assert_eq!(requested, size);
+            assert_eq!(negotiated, size);
+            client.disconnect().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_set_plc_time_epoch() {
+        // 2024-02-29T13:45:30Z，星期四
+        let date_time = S7Client::date_time_from_epoch(1_709_214_330).unwrap();
+        let (year, mon, mday, wday, yday) = (
+            date_time.tm_year,
+            date_time.tm_mon,
+            date_time.tm_mday,
+            date_time.tm_wday,
+            date_time.tm_yday,
+        );
+        let (hour, min, sec) = (date_time.tm_hour, date_time.tm_min, date_time.tm_sec);
+        assert_eq!((year, mon, mday, wday, yday), (124, 1, 29, 4, 59));
+        assert_eq!((hour, min, sec), (13, 45, 30));
+
+        assert!(S7Client::date_time_from_epoch(631_151_999).is_err());
+        assert!(S7Client::date_time_from_epoch(3_786_912_000).is_err());
+
+        // 本地服务端只应答设置请求而不保存时钟，这里只验证写入成功
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+        client.set_plc_time_epoch(1_709_214_330).unwrap();
+        assert!(client.set_plc_time_epoch(0).is_err());
+    }
+
+    #[test]
+    fn test_db_directory() {
+        let mut db_buff = [0u8; 16];
+        let mut db5_buff = [0u8; 8];
+        let (server, client) = connect_local(&mut db_buff);
+        server
+            .register_area(AreaCode::S7AreaDB, 5, &mut db5_buff)
+            .unwrap();
+
+        let entries: Vec<DbEntry> = client
+            .db_directory()
+            .unwrap()
+            .into_iter()
+            .collect::<Result<_>>()
+            .unwrap();
+        assert_eq!(
+            entries,
+            [
+                DbEntry {
+                    number: 1,
+                    size: 16,
+                    optimized: false,
+                },
+                DbEntry {
+                    number: 5,
+                    size: 8,
+                    optimized: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_download_reader() {
+        // 最小的 DB 区块: 36 字节头部 + 4 字节 MC7 代码 + 48 字节尾部
+        let mut block = vec![0u8; 88];
+        block[2] = 0x01;
+        block[5] = 0x0A;
+        block[6..8].copy_from_slice(&7u16.to_be_bytes());
+        block[8..12].copy_from_slice(&88u32.to_be_bytes());
+        block[34..36].copy_from_slice(&4u16.to_be_bytes());
+        let path = std::env::temp_dir().join(format!("snap7-download-{}.bin", std::process::id()));
+        std::fs::write(&path, &block).unwrap();
+
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 本地服务端不支持下载，以"需要密码"拒绝，说明区块已通过校验并开始传输
+        let err = client
+            .download_reader(-1, std::fs::File::open(&path).unwrap(), 88)
+            .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Snap7Error>().and_then(Snap7Error::code),
+            Some(errCliNeedPassword as i32)
+        );
+
+        let err = client
+            .download_reader(-1, std::fs::File::open(&path).unwrap(), 100)
+            .unwrap_err();
+        assert!(err.downcast_ref::<Snap7Error>().is_none());
+        assert!(client.download_reader(-1, &block[..], 0).is_err());
+        assert_eq!(
+            client
+                .download_reader(-1, std::io::repeat(0), 65537)
+                .unwrap_err()
+                .downcast_ref::<Snap7Error>()
+                .and_then(Snap7Error::code),
+            Some(errCliInvalidBlockSize as i32)
+        );
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_db_is_optimized() {
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        assert!(!client.db_is_optimized(1).unwrap());
+        assert!(client.db_is_optimized(2).is_err());
+    }
+
+    #[test]
+    fn test_db_write_checked() {
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        client.db_write_checked(1, 12, &mut [1, 2, 3, 4]).unwrap();
+        let err = client.db_write_checked(1, 14, &mut [5, 6, 7]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Snap7Error>(),
+            Some(&Snap7Error::OutOfBounds {
+                db_size: 16,
+                attempted_end: 17,
+            })
+        );
+        assert_eq!(client.db_sizes.lock().unwrap().get(&1), Some(&16));
+        assert!(client.db_write_checked(2, 0, &mut [0]).is_err());
+        drop(client);
+        assert_eq!(db_buff[12..16], [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_status_history() {
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 本地服务端模拟的诊断缓冲区: STOP -> STARTUP -> RUN 循环三次
+        let history = client.status_history().unwrap();
+        let statuses: Vec<CpuStatus> = history.iter().map(|(_, status)| *status).collect();
+        assert_eq!(
+            statuses,
+            [
+                CpuStatus::Run,
+                CpuStatus::Stop,
+                CpuStatus::Run,
+                CpuStatus::Stop,
+                CpuStatus::Run
+            ]
+        );
+        assert_eq!(history[1].0.to_string(), "1994-02-04 23:52:13.901 UTC");
+        assert!(history.windows(2).all(|w| w[0].0 >= w[1].0));
+    }
+
+    #[test]
+    fn test_retentive_info() {
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 本地服务端模拟的 CPU 315-2 PN/DP 默认配置
+        let info = client.retentive_info().unwrap();
+        assert_eq!(
+            info,
+            RetentiveInfo {
+                marker_bytes: 0..16,
+                timers: 0..0,
+                counters: 0..8,
+            }
+        );
+    }
+
+    #[test]
+    fn test_connect_tsap() {
+        let mut db_buff = [7u8; 4];
+        let (_server, port) = start_server(&mut [(AreaCode::S7AreaDB, 1, &mut db_buff)]);
+
+        // connect_tsap() 总是使用默认端口 102，这里在指定端口的客户端上走相同流程
+        let client = S7Client::create();
+        client
+            .set_param(InternalParam::RemotePort, InternalParamValue::U16(port))
+            .unwrap();
+        let client = client
+            .connect_tsap_with("127.0.0.1", Tsap::new(0x01, 0, 0), Tsap::new(0x01, 0, 2))
+            .unwrap();
+        let mut buff = [0u8; 4];
+        client.db_read(1, 0, 4, &mut buff).unwrap();
+        assert_eq!(buff, [7; 4]);
+        client.disconnect().unwrap();
+    }
+
+    #[test]
+    fn test_iso_exchange() {
+        let mut db_buff = [0xDEu8, 0xAD, 0xBE, 0xEF];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 读变量请求: DB1.DBB0，4 字节
+        let request = [
+            0x32, 0x01, 0x00, 0x00, 0x00, 0x07, 0x00, 0x0E, 0x00, 0x00, 0x04, 0x01, 0x12, 0x0A,
+            0x10, 0x02, 0x00, 0x04, 0x00, 0x01, 0x84, 0x00, 0x00, 0x00,
+        ];
+        let response = client.iso_exchange(&request).unwrap();
+        assert!(response.is_ok());
+        assert_eq!(response.rosctr, 0x03);
+        assert_eq!(response.function, 0x04);
+        assert_eq!(
+            response.data,
+            [0xFF, 0x04, 0x00, 0x20, 0xDE, 0xAD, 0xBE, 0xEF]
+        );
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Unit {
+        speed: f32,
+        count: i16,
+    }
+
+    impl S7Struct for Unit {
+        const SIZE: usize = 6;
+
+        fn to_bytes(&self, buff: &mut [u8]) -> std::result::Result<(), String> {
+            setters::set_real(buff, 0, self.speed);
+            setters::set_int(buff, 4, self.count);
+            std::result::Result::Ok(())
+        }
+
+        fn from_bytes(buff: &[u8]) -> std::result::Result<Self, String> {
+            std::result::Result::Ok(Unit {
+                speed: getters::get_real(buff, 0),
+                count: getters::get_int(buff, 4),
+            })
+        }
+    }
+
+    #[test]
+    fn test_read_db_range() {
+        let mut db1 = [0u8; 8];
+        let mut db2 = [0u8; 6];
+        Unit {
+            speed: 1.5,
+            count: 3,
+        }
+        .to_bytes(&mut db1)
+        .unwrap();
+        Unit {
+            speed: -2.0,
+            count: 9,
+        }
+        .to_bytes(&mut db2)
+        .unwrap();
+        let (server, client) = connect_local(&mut db1);
+        server
+            .register_area(AreaCode::S7AreaDB, 2, &mut db2)
+            .unwrap();
+
+        let units = client.read_db_range::<Unit>(1, 3).unwrap();
+        assert_eq!(units.len(), 3);
+        assert_eq!(
+            units[0].as_ref().unwrap(),
+            &Unit {
+                speed: 1.5,
+                count: 3
+            }
+        );
+        assert_eq!(
+            units[1].as_ref().unwrap(),
+            &Unit {
+                speed: -2.0,
+                count: 9
+            }
+        );
+        assert!(units[2].as_ref().unwrap_err().to_string().contains("DB3"));
+
+        assert!(client.read_db_range::<Unit>(3, 1).is_err());
+    }
+
+    #[test]
+    fn test_wait_for_bit() {
+        let mut db_buff = [0u8; 4];
+        let (server, client) = connect_local(&mut db_buff);
+        let poll = Duration::from_millis(10);
+
+        assert!(client
+            .wait_for_bit(1, 2, 6, true, Duration::from_millis(50), poll)
+            .is_err());
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                std::thread::sleep(Duration::from_millis(100));
+                let _lock = server.lock(AreaCode::S7AreaDB, 1).unwrap();
+                db_buff[2] = 0b0100_0000;
+            });
+            client
+                .wait_for_bit(1, 2, 6, true, Duration::from_secs(5), poll)
+                .unwrap();
+        });
+        client
+            .wait_for_bit(1, 2, 5, false, Duration::ZERO, poll)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_db_read_real_consistent() {
+        let mut db_buff = [0u8; 8];
+        db_buff[4..8].copy_from_slice(&12.5f32.to_be_bytes());
+        let (_server, client) = connect_local(&mut db_buff);
+
+        assert_eq!(client.db_read_real_consistent(1, 4, 0).unwrap(), 12.5);
+        assert!(client.db_read_real_consistent(1, 6, 3).is_err());
+    }
+
+    #[test]
+    fn test_cycle_time() {
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 本地服务端模拟的 CPU 315-2 PN/DP 的 OB1 启动信息
+        assert_eq!(
+            client.cycle_time().unwrap(),
+            CycleTime {
+                current: Duration::ZERO,
+                min: Duration::ZERO,
+                max: Duration::from_millis(1),
+            }
+        );
+    }
+
+    #[test]
+    fn test_params() {
+        let client = S7Client::create();
+        client
+            .set_param(InternalParam::RemotePort, InternalParamValue::U16(1102))
+            .unwrap();
+        client
+            .set_param(InternalParam::PingTimeout, InternalParamValue::I32(1234))
+            .unwrap();
+        client
+            .set_param(InternalParam::PDURequest, InternalParamValue::I32(240))
+            .unwrap();
+
+        let params = client.params().unwrap();
+        assert_eq!(params.remote_port, 1102);
+        assert_eq!(params.ping_timeout, Duration::from_millis(1234));
+        assert_eq!(params.pdu_request, 240);
+    }
+
+    #[test]
+    fn test_led_states() {
+        let mut db_buff = [0u8; 16];
+        let (_server, client) = connect_local(&mut db_buff);
+
+        // 本地服务端模拟的 CPU 只点亮 RUN 指示灯
+        let leds = client.led_states().unwrap();
+        assert_eq!(leds.len(), 7);
+        let run = leds.iter().find(|led| led.id == LedState::RUN).unwrap();
+        assert!(run.on);
+        assert_eq!(run.blink, LedBlink::None);
+        assert!(leds
+            .iter()
+            .filter(|led| led.id != LedState::RUN)
+            .all(|led| !led.on));
+        assert!(leds.iter().any(|led| led.id == LedState::MAINT));
+    }
+
+    #[test]
+    fn test_read_tags_resilient() {
+        use crate::utils::layout::S7Type;
+
+        // 20 个 STRING[38](每个 40 字节)，应答总长超出 PDU，需要逐项回退读取
+        let mut db_buff = vec![0u8; 800];
+        for (i, chunk) in db_buff.chunks_mut(40).enumerate() {
+            let text = format!("tag{}", i);
+            chunk[0] = 38;
+            chunk[1] = text.len() as u8;
+            chunk[2..2 + text.len()].copy_from_slice(text.as_bytes());
+        }
+        let (_server, client) = connect_local(&mut db_buff);
+        let tags: Vec<TagAddress> = (0..20)
+            .map(|i| {
+                format!("DB1.DBB{}", i * 40)
+                    .parse::<TagAddress>()
+                    .unwrap()
+                    .with_type(S7Type::String(38))
+            })
+            .collect();
+        let max_items = client.max_multi_var_items().unwrap();
+        assert!((1..=20).contains(&max_items));
+
+        let mut items: Vec<TS7DataItem> = Vec::new();
+        let mut buffers = vec![[0u8; 40]; max_items];
+        for (i, buff) in buffers.iter_mut().enumerate() {
+            items.push(TS7DataItem {
+                Area: AreaTable::S7AreaDB as c_int,
+                WordLen: WordLenTable::S7WLByte as c_int,
+                Result: 0,
+                DBNumber: 1,
+                Start: (i * 40) as c_int,
+                Amount: 40,
+                pdata: buff.as_mut_ptr() as *mut c_void,
+            });
+        }
+        client
+            .read_multi_vars(&mut items, max_items as i32)
+            .unwrap();
+        assert!(items.iter().any(|item| item.Result != 0));
+
+        let values = client.read_tags_resilient(&tags).unwrap();
+        assert_eq!(values.len(), 20);
+        for (i, value) in values.iter().enumerate() {
+            assert_eq!(*value, Value::String(format!("tag{}", i)));
+        }
+
+        let invalid = ["DB9.DBW0".parse::<TagAddress>().unwrap()];
+        assert!(client.read_tags_resilient(&invalid).is_err());
+    }
+
+    #[test]
+    fn test_pending_op() {
+        let (_server, port) = start_server_with(|server| {
+            server
+                .set_rw_area_callback(Some(|_, _, _, _, _| {
+                    std::thread::sleep(Duration::from_millis(300));
+                }))
+                .unwrap();
+        });
+        let client = connect_client(port);
+
+        let mut buff = [0u8; 4];
+        assert!(!client.is_busy());
+        assert_eq!(client.pending_op_code(), None);
+        client.as_db_read(1, 0, 4, &mut buff).unwrap();
+        assert!(client.is_busy());
+        assert_eq!(client.pending_op_code(), Some(AsyncOp::ReadArea as i32));
+        assert_eq!(client.pending_op(), Some(AsyncOp::ReadArea));
+        // 任务进行中再发起的调用失败，不覆盖记录的操作类型
+        let mut write_buff = [0u8; 4];
+        assert!(client.as_db_write(1, 0, 4, &mut write_buff).is_err());
+        assert_eq!(client.pending_op(), Some(AsyncOp::ReadArea));
+        assert_eq!(client.wait_as_completion(2000), 0);
+        assert!(!client.is_busy());
+        assert_eq!(client.pending_op_code(), None);
+    }
 }
@@ -0,0 +1,281 @@
+//
+// error.rs
+// Copyright (C) 2021 gmg137 <gmg137 AT live.com>
+// snap7-rs is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//          http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+// EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+// MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+//
+use crate::{
+    client::S7Client,
+    ffi::{
+        errCliInvalidDataSizeRecvd, errCliInvalidPlcAnswer, errNegotiatingPDU, errParInvalidPDU,
+        errParNegotiatingPDU,
+    },
+    partner::S7Partner,
+    server::S7Server,
+};
+use std::fmt;
+
+/// 错误类别
+///
+/// snap7 错误代码按位分层: 低 16 位为 TCP 错误(系统 errno)，16~19 位为 ISO 层错误，
+/// 高 12 位为 S7 层错误，其含义取决于产生错误的组件(客户端、服务端或伙伴)。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// TCP 套接字错误
+    Tcp,
+    /// ISO-on-TCP 层错误
+    Iso,
+    /// S7 协议错误(PDU 协商失败、无效应答等)
+    S7Protocol,
+    /// 客户端错误
+    Client,
+    /// 服务端错误
+    Server,
+    /// 伙伴错误
+    Partner,
+}
+
+/// snap7 错误代码中 TCP 层所占的位
+const TCP_ERROR_MASK: u32 = 0x0000_FFFF;
+/// snap7 错误代码中 ISO 层所占的位
+const ISO_ERROR_MASK: u32 = 0x000F_0000;
+/// snap7 错误代码中 S7 层所占的位
+const S7_ERROR_MASK: u32 = 0xFFF0_0000;
+
+/// Snap7 错误
+///
+/// 前置条件类错误由本库在调用 snap7 之前检查产生，Client/Server/Partner 携带 snap7 返回的错误代码，
+/// 均可以通过 `anyhow::Error::downcast_ref` 取得。
+///
+/// # Examples
+/// ```ignore
+/// if let Err(e) = client.compress(1000) {
+///     if let Some(Snap7Error::CpuNotStopped) = e.downcast_ref::<Snap7Error>() {
+///         client.plc_stop().unwrap();
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Snap7Error {
+    /// CPU 未处于 STOP 模式
+    CpuNotStopped,
+    /// CPU 已处于 RUN 模式
+    CpuAlreadyRunning,
+    /// 分块写入中途失败
+    PartialWrite {
+        /// 失败前已成功写入的字节数
+        written: usize,
+        /// snap7 返回的错误信息
+        message: String,
+    },
+    /// 写入后回读的位值与写入值不一致
+    BitMismatch {
+        /// 写入的值
+        expected: bool,
+        /// 回读的值
+        actual: bool,
+    },
+    /// 写入后回读的数据与写入数据不一致
+    DataMismatch {
+        /// 写入的数据
+        expected: Vec<u8>,
+        /// 回读的数据
+        actual: Vec<u8>,
+    },
+    /// 写入范围超出数据块(DB)的大小
+    OutOfBounds {
+        /// DB 大小(字节)
+        db_size: usize,
+        /// 写入范围的结束地址(不含)
+        attempted_end: usize,
+    },
+    /// 客户端 snap7 调用返回的错误
+    Client {
+        /// 错误代码
+        code: i32,
+        /// 错误信息
+        message: String,
+    },
+    /// 服务端 snap7 调用返回的错误
+    Server {
+        /// 错误代码
+        code: i32,
+        /// 错误信息
+        message: String,
+    },
+    /// 伙伴 snap7 调用返回的错误
+    Partner {
+        /// 错误代码
+        code: i32,
+        /// 错误信息
+        message: String,
+    },
+}
+
+impl Snap7Error {
+    pub(crate) fn client(code: i32) -> Self {
+        Snap7Error::Client {
+            code,
+            message: S7Client::error_text(code),
+        }
+    }
+
+    pub(crate) fn server(code: i32) -> Self {
+        Snap7Error::Server {
+            code,
+            message: S7Server::error_text(code),
+        }
+    }
+
+    pub(crate) fn partner(code: i32) -> Self {
+        Snap7Error::Partner {
+            code,
+            message: S7Partner::error_text(code),
+        }
+    }
+
+    /// snap7 返回的错误代码，前置条件类错误为 None
+    pub fn code(&self) -> Option<i32> {
+        match self {
+            Snap7Error::Client { code, .. }
+            | Snap7Error::Server { code, .. }
+            | Snap7Error::Partner { code, .. } => Some(*code),
+            _ => None,
+        }
+    }
+
+    ///
+    /// 按错误代码的位分层判断错误类别。
+    ///
+    /// 优先取最高的非零层: S7 层按组件归为 Client/Server/Partner(协议类错误归为 S7Protocol)，
+    /// 否则为 Iso，再否则为 Tcp。前置条件类错误由客户端检查产生，归为 Client。
+    ///
+    /// **返回值:**
+    ///
+    ///  - 错误类别
+    ///
+    pub fn class(&self) -> ErrorClass {
+        let (code, component) = match self {
+            Snap7Error::Client { code, .. } => (*code as u32, ErrorClass::Client),
+            Snap7Error::Server { code, .. } => (*code as u32, ErrorClass::Server),
+            Snap7Error::Partner { code, .. } => (*code as u32, ErrorClass::Partner),
+            _ => return ErrorClass::Client,
+        };
+        let s7_error = code & S7_ERROR_MASK;
+        if s7_error != 0 {
+            let protocol = match component {
+                ErrorClass::Client => [
+                    errNegotiatingPDU,
+                    errCliInvalidPlcAnswer,
+                    errCliInvalidDataSizeRecvd,
+                ]
+                .contains(&s7_error),
+                ErrorClass::Partner => [errParInvalidPDU, errParNegotiatingPDU].contains(&s7_error),
+                _ => false,
+            };
+            if protocol {
+                ErrorClass::S7Protocol
+            } else {
+                component
+            }
+        } else if code & ISO_ERROR_MASK != 0 {
+            ErrorClass::Iso
+        } else if code & TCP_ERROR_MASK != 0 {
+            ErrorClass::Tcp
+        } else {
+            component
+        }
+    }
+}
+
+impl fmt::Display for Snap7Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Snap7Error::CpuNotStopped => write!(
+                f,
+                "CPU is not in STOP mode, call plc_stop() before this operation"
+            ),
+            Snap7Error::CpuAlreadyRunning => write!(f, "CPU is already in RUN mode"),
+            Snap7Error::PartialWrite { written, message } => {
+                write!(f, "write failed after {} bytes: {}", written, message)
+            }
+            Snap7Error::BitMismatch { expected, actual } => {
+                write!(f, "bit read back as {} after writing {}", actual, expected)
+            }
+            Snap7Error::DataMismatch { expected, actual } => write!(
+                f,
+                "data read back as {:02X?} after writing {:02X?}",
+                actual, expected
+            ),
+            Snap7Error::OutOfBounds {
+                db_size,
+                attempted_end,
+            } => write!(
+                f,
+                "write ends at byte {} but the DB is only {} bytes",
+                attempted_end, db_size
+            ),
+            Snap7Error::Client { message, .. }
+            | Snap7Error::Server { message, .. }
+            | Snap7Error::Partner { message, .. } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for Snap7Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ffi::*;
+
+    #[test]
+    fn test_error_class() {
+        // 10061: WSAECONNREFUSED, 111: ECONNREFUSED
+        assert_eq!(Snap7Error::client(10061).class(), ErrorClass::Tcp);
+        assert_eq!(Snap7Error::partner(111).class(), ErrorClass::Tcp);
+        assert_eq!(
+            Snap7Error::client(errIsoConnect as i32).class(),
+            ErrorClass::Iso
+        );
+        assert_eq!(
+            Snap7Error::client((errIsoRecvPacket | 104) as i32).class(),
+            ErrorClass::Iso
+        );
+        assert_eq!(
+            Snap7Error::client(errNegotiatingPDU as i32).class(),
+            ErrorClass::S7Protocol
+        );
+        assert_eq!(
+            Snap7Error::partner(errParNegotiatingPDU as i32).class(),
+            ErrorClass::S7Protocol
+        );
+        assert_eq!(
+            Snap7Error::client(errCliAddressOutOfRange as i32).class(),
+            ErrorClass::Client
+        );
+        assert_eq!(
+            Snap7Error::server(errSrvCannotStart as i32).class(),
+            ErrorClass::Server
+        );
+        assert_eq!(
+            Snap7Error::partner(errParNotLinked as i32).class(),
+            ErrorClass::Partner
+        );
+        assert_eq!(Snap7Error::CpuNotStopped.class(), ErrorClass::Client);
+        assert_eq!(Snap7Error::CpuNotStopped.code(), None);
+
+        let err = Snap7Error::client(errCliItemNotAvailable as i32);
+        assert_eq!(err.code(), Some(errCliItemNotAvailable as i32));
+        assert_eq!(
+            err.to_string(),
+            S7Client::error_text(errCliItemNotAvailable as i32)
+        );
+    }
+}
@@ -293,7 +293,7 @@ fn bindgen_test_layout___fsid_t() {
         concat!("Alignment of ", stringify!(__fsid_t))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<__fsid_t>())).__val as *const _ as usize },
+        ::std::mem::offset_of!(__fsid_t, __val),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1181,7 +1181,7 @@ fn bindgen_test_layout_timespec() {
         concat!("Alignment of ", stringify!(timespec))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<timespec>())).tv_sec as *const _ as usize },
+        ::std::mem::offset_of!(timespec, tv_sec),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1191,7 +1191,7 @@ fn bindgen_test_layout_timespec() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<timespec>())).tv_nsec as *const _ as usize },
+        ::std::mem::offset_of!(timespec, tv_nsec),
         8usize,
         concat!(
             "Offset of field: ",
@@ -1222,7 +1222,7 @@ fn bindgen_test_layout_itimerspec() {
         concat!("Alignment of ", stringify!(itimerspec))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<itimerspec>())).it_interval as *const _ as usize },
+        ::std::mem::offset_of!(itimerspec, it_interval),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1232,7 +1232,7 @@ fn bindgen_test_layout_itimerspec() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<itimerspec>())).it_value as *const _ as usize },
+        ::std::mem::offset_of!(itimerspec, it_value),
         16usize,
         concat!(
             "Offset of field: ",
@@ -1269,7 +1269,7 @@ fn bindgen_test_layout___locale_struct() {
         concat!("Alignment of ", stringify!(__locale_struct))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<__locale_struct>())).__locales as *const _ as usize },
+        ::std::mem::offset_of!(__locale_struct, __locales),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1279,7 +1279,7 @@ fn bindgen_test_layout___locale_struct() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<__locale_struct>())).__ctype_b as *const _ as usize },
+        ::std::mem::offset_of!(__locale_struct, __ctype_b),
         104usize,
         concat!(
             "Offset of field: ",
@@ -1289,7 +1289,7 @@ fn bindgen_test_layout___locale_struct() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<__locale_struct>())).__ctype_tolower as *const _ as usize },
+        ::std::mem::offset_of!(__locale_struct, __ctype_tolower),
         112usize,
         concat!(
             "Offset of field: ",
@@ -1299,7 +1299,7 @@ fn bindgen_test_layout___locale_struct() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<__locale_struct>())).__ctype_toupper as *const _ as usize },
+        ::std::mem::offset_of!(__locale_struct, __ctype_toupper),
         120usize,
         concat!(
             "Offset of field: ",
@@ -1309,7 +1309,7 @@ fn bindgen_test_layout___locale_struct() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<__locale_struct>())).__names as *const _ as usize },
+        ::std::mem::offset_of!(__locale_struct, __names),
         128usize,
         concat!(
             "Offset of field: ",
@@ -1692,7 +1692,7 @@ fn bindgen_test_layout_TS7BlocksList() {
 }
 pub type PS7BlocksList = *mut TS7BlocksList;
 #[repr(C, packed)]
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub struct TS7BlockInfo {
     pub BlkType: ::std::os::raw::c_int,
     pub BlkNumber: ::std::os::raw::c_int,
@@ -1723,7 +1723,7 @@ fn bindgen_test_layout_TS7BlockInfo() {
         concat!("Alignment of ", stringify!(TS7BlockInfo))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7BlockInfo>())).CodeDate as *const _ as usize },
+        ::std::mem::offset_of!(TS7BlockInfo, CodeDate),
         40usize,
         concat!(
             "Offset of field: ",
@@ -1733,7 +1733,7 @@ fn bindgen_test_layout_TS7BlockInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7BlockInfo>())).IntfDate as *const _ as usize },
+        ::std::mem::offset_of!(TS7BlockInfo, IntfDate),
         51usize,
         concat!(
             "Offset of field: ",
@@ -1743,7 +1743,7 @@ fn bindgen_test_layout_TS7BlockInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7BlockInfo>())).Author as *const _ as usize },
+        ::std::mem::offset_of!(TS7BlockInfo, Author),
         62usize,
         concat!(
             "Offset of field: ",
@@ -1753,7 +1753,7 @@ fn bindgen_test_layout_TS7BlockInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7BlockInfo>())).Family as *const _ as usize },
+        ::std::mem::offset_of!(TS7BlockInfo, Family),
         71usize,
         concat!(
             "Offset of field: ",
@@ -1763,7 +1763,7 @@ fn bindgen_test_layout_TS7BlockInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7BlockInfo>())).Header as *const _ as usize },
+        ::std::mem::offset_of!(TS7BlockInfo, Header),
         80usize,
         concat!(
             "Offset of field: ",
@@ -1797,7 +1797,7 @@ fn bindgen_test_layout_TS7OrderCode() {
         concat!("Alignment of ", stringify!(TS7OrderCode))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7OrderCode>())).Code as *const _ as usize },
+        ::std::mem::offset_of!(TS7OrderCode, Code),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1807,7 +1807,7 @@ fn bindgen_test_layout_TS7OrderCode() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7OrderCode>())).V1 as *const _ as usize },
+        ::std::mem::offset_of!(TS7OrderCode, V1),
         21usize,
         concat!(
             "Offset of field: ",
@@ -1817,7 +1817,7 @@ fn bindgen_test_layout_TS7OrderCode() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7OrderCode>())).V2 as *const _ as usize },
+        ::std::mem::offset_of!(TS7OrderCode, V2),
         22usize,
         concat!(
             "Offset of field: ",
@@ -1827,7 +1827,7 @@ fn bindgen_test_layout_TS7OrderCode() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7OrderCode>())).V3 as *const _ as usize },
+        ::std::mem::offset_of!(TS7OrderCode, V3),
         23usize,
         concat!(
             "Offset of field: ",
@@ -1860,7 +1860,7 @@ fn bindgen_test_layout_TS7CpuInfo() {
         concat!("Alignment of ", stringify!(TS7CpuInfo))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7CpuInfo>())).ModuleTypeName as *const _ as usize },
+        ::std::mem::offset_of!(TS7CpuInfo, ModuleTypeName),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1870,7 +1870,7 @@ fn bindgen_test_layout_TS7CpuInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7CpuInfo>())).SerialNumber as *const _ as usize },
+        ::std::mem::offset_of!(TS7CpuInfo, SerialNumber),
         33usize,
         concat!(
             "Offset of field: ",
@@ -1880,7 +1880,7 @@ fn bindgen_test_layout_TS7CpuInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7CpuInfo>())).ASName as *const _ as usize },
+        ::std::mem::offset_of!(TS7CpuInfo, ASName),
         58usize,
         concat!(
             "Offset of field: ",
@@ -1890,7 +1890,7 @@ fn bindgen_test_layout_TS7CpuInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7CpuInfo>())).Copyright as *const _ as usize },
+        ::std::mem::offset_of!(TS7CpuInfo, Copyright),
         83usize,
         concat!(
             "Offset of field: ",
@@ -1900,7 +1900,7 @@ fn bindgen_test_layout_TS7CpuInfo() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7CpuInfo>())).ModuleName as *const _ as usize },
+        ::std::mem::offset_of!(TS7CpuInfo, ModuleName),
         110usize,
         concat!(
             "Offset of field: ",
@@ -1972,7 +1972,7 @@ fn bindgen_test_layout_TS7SZL() {
         concat!("Alignment of ", stringify!(TS7SZL))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7SZL>())).Header as *const _ as usize },
+        ::std::mem::offset_of!(TS7SZL, Header),
         0usize,
         concat!(
             "Offset of field: ",
@@ -1982,7 +1982,7 @@ fn bindgen_test_layout_TS7SZL() {
         )
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7SZL>())).Data as *const _ as usize },
+        ::std::mem::offset_of!(TS7SZL, Data),
         4usize,
         concat!(
             "Offset of field: ",
@@ -2012,7 +2012,7 @@ fn bindgen_test_layout_TS7SZLList() {
         concat!("Alignment of ", stringify!(TS7SZLList))
     );
     assert_eq!(
-        unsafe { &(*(::std::ptr::null::<TS7SZLList>())).Header as *const _ as usize },
+        ::std::mem::offset_of!(TS7SZLList, Header),
         0usize,
         concat!(
             "Offset of field: ",
@@ -2772,7 +2772,7 @@ pub type pfn_RWAreaCallBack = ::std::option::Option<
         Operation: ::std::os::raw::c_int,
         PTag: PS7Tag,
         pUsrData: *mut ::std::os::raw::c_void,
-    ),
+    ) -> ::std::os::raw::c_int,
 >;
 extern "C" {
     pub fn Srv_Create() -> S7Object;
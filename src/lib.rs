@@ -9,15 +9,34 @@
 // MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
 // See the Mulan PSL v2 for more details.
 //
+mod builder;
 mod client;
+mod error;
+/// snap7 原始 FFI 绑定(extern 函数声明及 C 结构体)。
+///
+/// 仅在启用 `raw-ffi` 特性时公开，供需要直接调用 snap7 C 接口的场景使用。
+/// 其中的函数均为 `unsafe`，句柄的创建与释放需由调用者自行管理，
+/// 不要对 `S7Client`/`S7Server`/`S7Partner` 内部持有的句柄调用 `*_Destroy`。
+#[cfg(feature = "raw-ffi")]
+pub mod ffi;
+#[cfg(not(feature = "raw-ffi"))]
 mod ffi;
 mod model;
+mod multi_write;
 mod partner;
+mod pool;
+mod reliable;
 mod server;
+mod stream;
+#[cfg(test)]
+mod test_support;
 pub mod utils;
 
 pub use crate::ffi::{
     DateTime, TS7BlockInfo, TS7BlocksList, TS7BlocksOfType, TS7CpInfo, TS7CpuInfo, TS7DataItem,
     TS7OrderCode, TS7Protection, TSrvEvent,
 };
-pub use {client::*, model::*, partner::*, server::*};
+pub use {
+    builder::*, client::*, error::*, model::*, multi_write::*, partner::*, pool::*, reliable::*,
+    server::*, stream::*,
+};
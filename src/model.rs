@@ -11,6 +11,13 @@
 // See the Mulan PSL v2 for more details.
 //
 #![allow(warnings)]
+use crate::ffi::{
+    CodeControlColdStart, CodeControlCompress, CodeControlCpyRamRom, CodeControlInsDel,
+    CodeControlStop, CodeControlWarmStart, TS7Protection, TS7Tag, TSrvEvent, CONNTYPE_OP,
+    CONNTYPE_PG,
+};
+use std::os::raw::c_int;
+
 pub const EVC_SERVER_STARTED: u32 = 0x00000001;
 pub const EVC_SERVER_STOPPED: u32 = 0x00000002;
 pub const EVC_LISTENER_CANNOT_START: u32 = 0x00000004;
@@ -44,13 +51,148 @@ pub const EVC_RESERVED_20000000: u32 = 0x20000000;
 pub const EVC_RESERVED_40000000: u32 = 0x40000000;
 pub const EVC_RESERVED_80000000: u32 = 0x80000000;
 
+/// CPU 状态: 未知
+pub const S7_CPU_STATUS_UNKNOWN: i32 = 0x00;
+/// CPU 状态: 运行
+pub const S7_CPU_STATUS_RUN: i32 = 0x08;
+/// CPU 状态: 停止
+pub const S7_CPU_STATUS_STOP: i32 = 0x04;
+
+/// 服务端读写回调操作: 读
+pub const OPERATION_READ: i32 = 0;
+/// 服务端读写回调操作: 写
+pub const OPERATION_WRITE: i32 = 1;
+
 /// 客户端连接类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConnType {
     PG,
     OP,
     S7Basic(u16),
 }
 
+impl ConnType {
+    ///
+    /// 连接类型对应的原始资源代码(PG 为 0x01，OP 为 0x02，S7Basic 为其携带的值)。
+    ///
+    pub fn resource(&self) -> u16 {
+        match *self {
+            ConnType::PG => CONNTYPE_PG,
+            ConnType::OP => CONNTYPE_OP,
+            ConnType::S7Basic(v) => v,
+        }
+    }
+
+    ///
+    /// 由原始资源代码构造连接类型，0x01/0x02 以外的值均为 S7Basic。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - value: 原始资源代码
+    ///
+    pub fn from_resource(value: u16) -> ConnType {
+        match value {
+            CONNTYPE_PG => ConnType::PG,
+            CONNTYPE_OP => ConnType::OP,
+            v => ConnType::S7Basic(v),
+        }
+    }
+}
+
+/// TSAP(传输服务访问点)
+///
+/// 高字节为连接资源(0x01 PG、0x02 OP、0x03 S7Basic，伙伴通讯常用 0x10 及以上)，
+/// 低字节为机架号 * 0x20 + 插槽号。
+///
+/// # Examples
+/// ```
+/// use rust_snap7::Tsap;
+///
+/// let tsap = Tsap::new(0x10, 0, 2);
+/// assert_eq!(tsap.value(), 0x1002);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tsap(u16);
+
+impl Tsap {
+    ///
+    /// 由连接资源、机架号和插槽号计算 TSAP。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - connection_resource: 连接资源
+    ///  - rack: 机架号(0..=7)
+    ///  - slot: 插槽号(0..=31)
+    ///
+    /// `注：机架号或插槽号超出范围时 panic。`
+    ///
+    pub fn new(connection_resource: u8, rack: u8, slot: u8) -> Tsap {
+        assert!(rack < 8, "rack {} out of range 0..=7", rack);
+        assert!(slot < 32, "slot {} out of range 0..=31", slot);
+        Tsap(u16::from_be_bytes([
+            connection_resource,
+            rack * 0x20 + slot,
+        ]))
+    }
+
+    /// 原始 TSAP 值
+    pub fn value(&self) -> u16 {
+        self.0
+    }
+
+    /// 连接资源(高字节)
+    pub fn connection_resource(&self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    /// 机架号
+    pub fn rack(&self) -> u8 {
+        (self.0 & 0xFF) as u8 >> 5
+    }
+
+    /// 插槽号
+    pub fn slot(&self) -> u8 {
+        (self.0 & 0x1F) as u8
+    }
+}
+
+impl From<u16> for Tsap {
+    fn from(value: u16) -> Self {
+        Tsap(value)
+    }
+}
+
+impl From<Tsap> for u16 {
+    fn from(tsap: Tsap) -> Self {
+        tsap.0
+    }
+}
+
+/// CPU 系列
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuFamily {
+    S7200,
+    S7300,
+    S7400,
+    S71200,
+    S71500,
+}
+
+impl CpuFamily {
+    ///
+    /// 该系列 CPU 最通用的连接类型。
+    ///
+    /// S7-1200/1500 为 HMI 预留的 OP 连接资源比 PG 多，且 PG 资源常被编程软件占用，
+    /// 因此使用 OP；其它系列使用 snap7 默认的 PG。
+    ///
+    pub fn default_connection_type(&self) -> ConnType {
+        match self {
+            CpuFamily::S71200 | CpuFamily::S71500 => ConnType::OP,
+            CpuFamily::S7200 | CpuFamily::S7300 | CpuFamily::S7400 => ConnType::PG,
+        }
+    }
+}
+
 /// 服务端掩码类型
 pub enum MaskKind {
     /// 事件
@@ -60,6 +202,7 @@ pub enum MaskKind {
 }
 
 /// 服务端区块类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AreaCode {
     /// 输入(Inputs)
     S7AreaPE = 0,
@@ -75,8 +218,98 @@ pub enum AreaCode {
     S7AreaDB = 5,
 }
 
+impl AreaCode {
+    ///
+    /// 将原始区块代码转换为 AreaCode。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - code: 原始区块代码
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 对应的 AreaCode
+    ///  - None: 无效的区块代码
+    ///
+    pub fn from_raw(code: c_int) -> Option<AreaCode> {
+        match code {
+            0 => Some(AreaCode::S7AreaPE),
+            1 => Some(AreaCode::S7AreaPA),
+            2 => Some(AreaCode::S7AreaMK),
+            3 => Some(AreaCode::S7AreaCT),
+            4 => Some(AreaCode::S7AreaTM),
+            5 => Some(AreaCode::S7AreaDB),
+            _ => None,
+        }
+    }
+
+    /// 区块的名称，如 "DB"、"Inputs"、"Merkers"，用于日志和界面显示
+    pub fn name(&self) -> &'static str {
+        match self {
+            AreaCode::S7AreaPE => "Inputs",
+            AreaCode::S7AreaPA => "Outputs",
+            AreaCode::S7AreaMK => "Merkers",
+            AreaCode::S7AreaCT => "Counters",
+            AreaCode::S7AreaTM => "Timers",
+            AreaCode::S7AreaDB => "DB",
+        }
+    }
+}
+
+impl std::fmt::Display for AreaCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 具有固定 S7 字节布局的结构体，可与 DB 缓冲区相互转换。
+///
+/// 目前需要手动实现，通常借助 utils::getters/utils::setters 按字段偏移读写。
+///
+/// # Examples
+/// ```
+/// use rust_snap7::{utils::{getters, setters}, S7Struct};
+///
+/// struct Motor {
+///     speed: f32,
+///     running: bool,
+/// }
+///
+/// impl S7Struct for Motor {
+///     const SIZE: usize = 5;
+///
+///     fn to_bytes(&self, buff: &mut [u8]) -> Result<(), String> {
+///         setters::set_real(buff, 0, self.speed);
+///         setters::set_bool(buff, 4, 0, self.running)
+///     }
+///
+///     fn from_bytes(buff: &[u8]) -> Result<Self, String> {
+///         Ok(Motor {
+///             speed: getters::get_real(buff, 0),
+///             running: getters::get_bool(buff, 4, 0)?,
+///         })
+///     }
+/// }
+///
+/// let mut buff = [0u8; Motor::SIZE];
+/// Motor { speed: 1.5, running: true }.to_bytes(&mut buff).unwrap();
+/// let motor = Motor::from_bytes(&buff).unwrap();
+/// assert_eq!(motor.speed, 1.5);
+/// assert!(motor.running);
+/// ```
+pub trait S7Struct: Sized {
+    /// 结构体在 DB 中占用的字节数
+    const SIZE: usize;
+
+    /// 将结构体写入长度为 SIZE 的缓冲区
+    fn to_bytes(&self, buff: &mut [u8]) -> Result<(), String>;
+
+    /// 从长度为 SIZE 的缓冲区解码结构体
+    fn from_bytes(buff: &[u8]) -> Result<Self, String>;
+}
+
 /// Snap 7 内部参数
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum InternalParam {
     /// Socket 本地端口
     LocalPort = 1,
@@ -118,8 +351,137 @@ pub enum InternalParamValue {
     U32(u32),
 }
 
+impl InternalParamValue {
+    /// 端口、ISO 引用和 TSAP 参数的值
+    pub(crate) fn as_u16(&self) -> Option<u16> {
+        match *self {
+            InternalParamValue::U16(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// PDU 长度、最大客户端数等整数参数的值
+    pub(crate) fn as_i32(&self) -> Option<i32> {
+        match *self {
+            InternalParamValue::I32(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// 以毫秒为单位的超时、间隔参数的值
+    pub(crate) fn as_millis(&self) -> Option<std::time::Duration> {
+        let ms = match *self {
+            InternalParamValue::I32(v) => u64::try_from(v).ok()?,
+            InternalParamValue::U32(v) => v as u64,
+            InternalParamValue::U16(_) => return None,
+        };
+        Some(std::time::Duration::from_millis(ms))
+    }
+}
+
+/// 通过 get_param()/set_param() 读写内部参数的对象(客户端、服务端和伙伴)
+pub(crate) trait ParamAccess {
+    fn get_param(&self, param: InternalParam, value: &mut InternalParamValue)
+        -> anyhow::Result<()>;
+
+    fn set_param(&self, param: InternalParam, value: InternalParamValue) -> anyhow::Result<()>;
+
+    /// 读取参数并用 convert 转换为具体类型，值的类型不符时返回错误
+    fn param<T>(
+        &self,
+        param: InternalParam,
+        convert: fn(&InternalParamValue) -> Option<T>,
+    ) -> anyhow::Result<T> {
+        let mut value = InternalParamValue::I32(0);
+        self.get_param(param, &mut value)?;
+        convert(&value)
+            .ok_or_else(|| anyhow::anyhow!("unexpected value {:?} for {:?}", value, param))
+    }
+
+    /// 以毫秒精度设置超时、间隔参数
+    fn set_millis_param(
+        &self,
+        param: InternalParam,
+        value: std::time::Duration,
+    ) -> anyhow::Result<()> {
+        if value.as_millis() > i32::MAX as u128 {
+            anyhow::bail!("{:?} too large: {:?}", param, value);
+        }
+        self.set_param(param, InternalParamValue::I32(value.as_millis() as i32))
+    }
+}
+
+/// S7Client::params() 返回的客户端内部参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientParams {
+    /// Socket 远程端口
+    pub remote_port: u16,
+    /// Ping 超时
+    pub ping_timeout: std::time::Duration,
+    /// Socket 发送超时
+    pub send_timeout: std::time::Duration,
+    /// Socket 接收超时
+    pub recv_timeout: std::time::Duration,
+    /// Socket 作业间隔
+    pub work_interval: std::time::Duration,
+    /// ISOTcp Source reference
+    pub src_ref: u16,
+    /// ISOTcp Destination reference
+    pub dst_ref: u16,
+    /// ISOTcp Source TSAP
+    pub src_tsap: u16,
+    /// 初始 PDU 请求长度
+    pub pdu_request: i32,
+}
+
+/// S7Server::params() 返回的服务端内部参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerParams {
+    /// Socket 本地端口
+    pub local_port: u16,
+    /// Socket 作业间隔
+    pub work_interval: std::time::Duration,
+    /// 允许的最大客户端数
+    pub max_clients: i32,
+    /// 初始 PDU 请求长度
+    pub pdu_request: i32,
+}
+
+/// S7Partner::params() 返回的伙伴内部参数
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PartnerParams {
+    /// Socket 本地端口
+    pub local_port: u16,
+    /// Socket 远程端口
+    pub remote_port: u16,
+    /// Ping 超时
+    pub ping_timeout: std::time::Duration,
+    /// Socket 发送超时
+    pub send_timeout: std::time::Duration,
+    /// Socket 接收超时
+    pub recv_timeout: std::time::Duration,
+    /// Socket 作业间隔
+    pub work_interval: std::time::Duration,
+    /// ISOTcp Source reference
+    pub src_ref: u16,
+    /// ISOTcp Destination reference
+    pub dst_ref: u16,
+    /// ISOTcp Source TSAP
+    pub src_tsap: u16,
+    /// 初始 PDU 请求长度
+    pub pdu_request: i32,
+    /// BSend 发送超时
+    pub bsend_timeout: std::time::Duration,
+    /// BRecv 接收超时
+    pub brecv_timeout: std::time::Duration,
+    /// 断线恢复时间
+    pub recovery_time: std::time::Duration,
+    /// 伙伴存活检测时间
+    pub keep_alive_time: std::time::Duration,
+}
+
 /// Area 表
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AreaTable {
     /// 输入(Inputs)
     S7AreaPE = 0x81,
@@ -135,8 +497,118 @@ pub enum AreaTable {
     S7AreaTM = 0x1d,
 }
 
+impl AreaTable {
+    ///
+    /// 将原始 Area 代码(如 TS7Tag.Area)转换为 AreaTable。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - code: 原始 Area 代码
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 对应的 AreaTable
+    ///  - None: 无效的 Area 代码
+    ///
+    pub fn from_raw(code: c_int) -> Option<AreaTable> {
+        match code {
+            0x81 => Some(AreaTable::S7AreaPE),
+            0x82 => Some(AreaTable::S7AreaPA),
+            0x83 => Some(AreaTable::S7AreaMK),
+            0x84 => Some(AreaTable::S7AreaDB),
+            0x1c => Some(AreaTable::S7AreaCT),
+            0x1d => Some(AreaTable::S7AreaTM),
+            _ => None,
+        }
+    }
+
+    /// 区域的名称，如 "DB"、"Inputs"、"Merkers"，用于日志和界面显示
+    pub fn name(&self) -> &'static str {
+        match self {
+            AreaTable::S7AreaPE => "Inputs",
+            AreaTable::S7AreaPA => "Outputs",
+            AreaTable::S7AreaMK => "Merkers",
+            AreaTable::S7AreaDB => "DB",
+            AreaTable::S7AreaCT => "Counters",
+            AreaTable::S7AreaTM => "Timers",
+        }
+    }
+}
+
+impl std::fmt::Display for AreaTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// 待写入的标签(按字节写入)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagWrite {
+    /// 写入区域
+    pub area: AreaTable,
+    /// 数据块(DB)编号，区域不为 S7AreaDB 时为 0
+    pub db_number: i32,
+    /// 起始字节地址
+    pub start: i32,
+    /// 待写入的数据
+    pub data: Vec<u8>,
+}
+
+/// 服务端收到的客户端读写请求
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RwRequest {
+    /// 客户端句柄
+    pub sender: i32,
+    /// 请求的区域，无法识别时为 None
+    pub area: Option<AreaTable>,
+    /// 数据块(DB)编号，仅当区域为 S7AreaDB 时为 Some
+    pub db_number: Option<u16>,
+    /// 起始地址，以字节表示(位访问时以比特表示，定时器/计数器时为元素序号)
+    pub start: i32,
+    /// 元素数量
+    pub amount: i32,
+    /// 原始 WordLen 代码
+    pub word_len: i32,
+}
+
+impl RwRequest {
+    ///
+    /// 由回调中的 TS7Tag 构造请求。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - sender: 客户端句柄
+    ///  - tag: TS7Tag 结构体
+    ///
+    pub fn from_tag(sender: i32, tag: &TS7Tag) -> RwRequest {
+        let area = AreaTable::from_raw(tag.Area);
+        RwRequest {
+            sender,
+            area,
+            db_number: match area {
+                Some(AreaTable::S7AreaDB) => u16::try_from(tag.DBNumber).ok(),
+                _ => None,
+            },
+            start: tag.Start,
+            amount: tag.Size,
+            word_len: tag.WordLen,
+        }
+    }
+
+    /// 请求的数据长度(字节)。
+    pub fn byte_size(&self) -> usize {
+        let element_size = match self.word_len {
+            0x01 | 0x02 | 0x03 => 1,
+            0x04 | 0x05 | 0x1c | 0x1d => 2,
+            0x06 | 0x07 | 0x08 => 4,
+            _ => 0,
+        };
+        element_size * self.amount.max(0) as usize
+    }
+}
+
 /// WordLen 表
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WordLenTable {
     /// 字节长度 1
     S7WLBit = 0x01,
@@ -154,8 +626,60 @@ pub enum WordLenTable {
     S7WLTimer = 0x1d,
 }
 
+impl WordLenTable {
+    ///
+    /// 将原始 WordLen 代码(如 TS7DataItem.WordLen)转换为 WordLenTable。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - code: 原始 WordLen 代码
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 对应的 WordLenTable
+    ///  - None: 无效的 WordLen 代码
+    ///
+    pub fn from_raw(code: c_int) -> Option<WordLenTable> {
+        match code {
+            0x01 => Some(WordLenTable::S7WLBit),
+            0x02 => Some(WordLenTable::S7WLByte),
+            0x04 => Some(WordLenTable::S7WLWord),
+            0x06 => Some(WordLenTable::S7WLDWord),
+            0x08 => Some(WordLenTable::S7WLReal),
+            0x1c => Some(WordLenTable::S7WLCounter),
+            0x1d => Some(WordLenTable::S7WLTimer),
+            _ => None,
+        }
+    }
+}
+
+///
+/// 计算 read_area()/write_area() 所需的缓冲区字节数。
+///
+/// **输入参数:**
+///
+///  - area: 读写的区域
+///  - word_len: 数据单元类型
+///  - amount: 数据单元数量
+///
+/// **返回值:**
+///
+///  - 缓冲区字节数
+///
+/// `注：定时器和计数器区域总是按每个元素 2 字节传输(即 amount*2)，与 word_len 无关；S7WLBit 每个位占 1 字节。`
+///
+pub fn required_buffer_size(area: AreaTable, word_len: WordLenTable, amount: usize) -> usize {
+    let element_size = match (area, word_len) {
+        (AreaTable::S7AreaTM | AreaTable::S7AreaCT, _) => 2,
+        (_, WordLenTable::S7WLBit | WordLenTable::S7WLByte) => 1,
+        (_, WordLenTable::S7WLWord | WordLenTable::S7WLCounter | WordLenTable::S7WLTimer) => 2,
+        (_, WordLenTable::S7WLDWord | WordLenTable::S7WLReal) => 4,
+    };
+    element_size * amount
+}
+
 /// 区块类型
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BlockType {
     BlockOB = 0x38,
     BlockDB = 0x41,
@@ -165,3 +689,1108 @@ pub enum BlockType {
     BlockFB = 0x45,
     BlockSFB = 0x46,
 }
+
+/// 伙伴连接信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ConnectionInfo {
+    /// 本地 TSAP
+    pub local_tsap: u16,
+    /// 远程 TSAP(snap7 不提供读取接口，取自 start_to 传入的值)
+    pub remote_tsap: u16,
+    /// 本地端口
+    pub local_port: u16,
+    /// 远程端口
+    pub remote_port: u16,
+}
+
+/// CP（通信处理器）信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpInfo {
+    /// 最大 PDU 长度(字节)
+    pub max_pdu_length: i32,
+    /// 最大连接数
+    pub max_connections: i32,
+    /// MPI 最大波特率(bit/s)
+    pub max_mpi_rate: i32,
+    /// 总线最大波特率(bit/s)
+    pub max_bus_rate: i32,
+}
+
+/// 组件标识(SZL 0x001C)
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ComponentIdentification {
+    /// 自动化系统名称
+    pub as_name: String,
+    /// 模块名称
+    pub module_name: String,
+    /// 设备标识(工厂名称)
+    pub plant_designation: String,
+    /// 版权信息
+    pub copyright: String,
+    /// 模块序列号
+    pub serial_number: String,
+    /// 模块类型名称
+    pub module_type_name: String,
+    /// 存储卡序列号
+    pub memory_card_serial_number: String,
+    /// 位置标识
+    pub location_designation: String,
+}
+
+impl ComponentIdentification {
+    ///
+    /// 从 SZL 0x001C 的数据记录中解码组件标识，字符串去除尾部的空字符和空格。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - data: SZL 数据(不含头)
+    ///  - record_len: 每条记录的长度(LENTHDR)
+    ///  - record_count: 记录数量(N_DR)
+    ///
+    pub fn from_szl(data: &[u8], record_len: usize, record_count: usize) -> Self {
+        let mut identification = ComponentIdentification::default();
+        if record_len < 2 {
+            return identification;
+        }
+        for record in data.chunks_exact(record_len).take(record_count) {
+            let text = String::from_utf8_lossy(&record[2..])
+                .trim_end_matches(|c: char| c == '\0' || c == ' ')
+                .to_string();
+            match u16::from_be_bytes([record[0], record[1]]) {
+                0x0001 => identification.as_name = text,
+                0x0002 => identification.module_name = text,
+                0x0003 => identification.plant_designation = text,
+                0x0004 => identification.copyright = text,
+                0x0005 => identification.serial_number = text,
+                0x0007 => identification.module_type_name = text,
+                0x0008 => identification.memory_card_serial_number = text,
+                0x000B => identification.location_designation = text,
+                _ => {}
+            }
+        }
+        identification
+    }
+}
+
+/// CPU 存储器使用情况(SZL 0x0013)，单位为字节
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryInfo {
+    /// 工作存储器总大小
+    pub work_total: u32,
+    /// 工作存储器空闲大小
+    pub work_free: u32,
+    /// 装载存储器总大小(集成与插入的存储卡之和)
+    pub load_total: u32,
+    /// 装载存储器空闲大小
+    pub load_free: u32,
+}
+
+impl MemoryInfo {
+    ///
+    /// 从 SZL 0x0013 的数据记录中解码存储器使用情况。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - data: SZL 数据(不含头)
+    ///  - record_len: 每条记录的长度(LENTHDR)，至少 36 字节
+    ///  - record_count: 记录数量(N_DR)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 存储器使用情况
+    ///  - None: 记录格式不符或缺少工作存储器记录
+    ///
+    pub fn from_szl(data: &[u8], record_len: usize, record_count: usize) -> Option<Self> {
+        if record_len < 36 {
+            return None;
+        }
+        let dword = |record: &[u8], offset: usize| {
+            u32::from_be_bytes(record[offset..offset + 4].try_into().unwrap())
+        };
+        let mut info = MemoryInfo::default();
+        let mut has_work_memory = false;
+        for record in data.chunks_exact(record_len).take(record_count) {
+            // 总大小，以及易失(RAM)与非易失部分已使用的字节数
+            let size = dword(record, 4);
+            let used = dword(record, 16).saturating_add(dword(record, 28));
+            let free = size.saturating_sub(used);
+            match u16::from_be_bytes([record[0], record[1]]) {
+                0x0001 => {
+                    info.work_total = size;
+                    info.work_free = free;
+                    has_work_memory = true;
+                }
+                0x0002 | 0x0003 => {
+                    info.load_total = info.load_total.saturating_add(size);
+                    info.load_free = info.load_free.saturating_add(free);
+                }
+                _ => {}
+            }
+        }
+        has_work_memory.then_some(info)
+    }
+}
+
+/// CPU 指示灯的闪烁状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LedBlink {
+    /// 不闪烁
+    None,
+    /// 正常闪烁(2Hz)
+    Normal,
+    /// 慢速闪烁(0.5Hz)
+    Slow,
+    /// 无法识别的闪烁代码
+    Other(u8),
+}
+
+/// CPU 指示灯状态(SZL 0x0074 的一条记录)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedState {
+    /// 机架/CPU 编号(cpu_led_ID 的高字节)
+    pub rack: u8,
+    /// 指示灯 ID(cpu_led_ID 的低字节)，如 1=SF、4=RUN、5=STOP、0x15=MAINT
+    pub id: u8,
+    /// 是否点亮
+    pub on: bool,
+    /// 闪烁状态
+    pub blink: LedBlink,
+}
+
+impl LedState {
+    /// 指示灯 SF(组故障)
+    pub const SF: u8 = 0x01;
+    /// 指示灯 RUN
+    pub const RUN: u8 = 0x04;
+    /// 指示灯 STOP
+    pub const STOP: u8 = 0x05;
+    /// 指示灯 FRCE(强制)
+    pub const FRCE: u8 = 0x06;
+    /// 指示灯 BUS1F
+    pub const BUS1F: u8 = 0x0B;
+    /// 指示灯 BUS2F
+    pub const BUS2F: u8 = 0x0C;
+    /// 指示灯 MAINT(维护)
+    pub const MAINT: u8 = 0x15;
+
+    ///
+    /// 从 SZL 0x0074 的数据中解码所有指示灯状态。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - data: SZL 数据(不含头)
+    ///  - record_len: 每条记录的长度(LENTHDR)，至少 4 字节
+    ///  - record_count: 记录数量(N_DR)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 指示灯状态列表
+    ///  - None: 记录格式不符
+    ///
+    pub fn from_szl(data: &[u8], record_len: usize, record_count: usize) -> Option<Vec<Self>> {
+        if record_len < 4 {
+            return None;
+        }
+        // 记录: cpu_led_ID(WORD)、led_on(BYTE)、led_blink(BYTE)
+        Some(
+            data.chunks_exact(record_len)
+                .take(record_count)
+                .map(|record| LedState {
+                    rack: record[0],
+                    id: record[1],
+                    on: record[2] == 1,
+                    blink: match record[3] {
+                        0 => LedBlink::None,
+                        1 => LedBlink::Normal,
+                        2 => LedBlink::Slow,
+                        other => LedBlink::Other(other),
+                    },
+                })
+                .collect(),
+        )
+    }
+}
+
+/// OB1 扫描周期时间(SZL 0x0222 INDEX 1，即 OB1 的启动信息)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CycleTime {
+    /// 上一个扫描周期的时间
+    pub current: std::time::Duration,
+    /// 自上次启动以来最短的扫描周期
+    pub min: std::time::Duration,
+    /// 自上次启动以来最长的扫描周期
+    pub max: std::time::Duration,
+}
+
+impl CycleTime {
+    ///
+    /// 从 SZL 0x0222 的中断状态记录中解码 OB1 的扫描周期时间。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - data: SZL 数据(不含头)
+    ///  - record_len: 每条记录的长度(LENTHDR)，至少 20 字节
+    ///  - record_count: 记录数量(N_DR)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 扫描周期时间
+    ///  - None: 记录格式不符或不是 OB1 的记录
+    ///
+    pub fn from_szl(data: &[u8], record_len: usize, record_count: usize) -> Option<Self> {
+        if record_len < 20 || record_count == 0 || data.len() < record_len {
+            return None;
+        }
+        // 启动信息: 事件类别、扫描、优先级、OB 号、保留(2 字节)，
+        // 随后为上一周期、最短周期、最长周期(INT，毫秒)
+        if data[3] != 1 {
+            return None;
+        }
+        let millis = |offset: usize| {
+            std::time::Duration::from_millis(
+                u16::from_be_bytes([data[offset], data[offset + 1]]) as u64
+            )
+        };
+        Some(CycleTime {
+            current: millis(6),
+            min: millis(8),
+            max: millis(10),
+        })
+    }
+}
+
+/// CPU 保持性(掉电保持)存储区范围(SZL 0x0014)
+///
+/// 保持性区域总是从地址 0 开始，例如默认配置的 CPU 315 为 MB0~MB15 与 C0~C7。
+/// 数据块的保持性是各 DB 的块属性，不在 SZL 中报告。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RetentiveInfo {
+    /// 保持性位存储器(M)字节地址范围
+    pub marker_bytes: std::ops::Range<usize>,
+    /// 保持性定时器(T)编号范围
+    pub timers: std::ops::Range<u16>,
+    /// 保持性计数器(C)编号范围
+    pub counters: std::ops::Range<u16>,
+}
+
+impl RetentiveInfo {
+    ///
+    /// 从 SZL 0x0014 的数据记录中解码保持性区域。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - data: SZL 数据(不含头)
+    ///  - record_len: 每条记录的长度(LENTHDR)，至少 8 字节
+    ///  - record_count: 记录数量(N_DR)
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 保持性区域
+    ///  - None: 记录格式不符或缺少位存储器记录
+    ///
+    pub fn from_szl(data: &[u8], record_len: usize, record_count: usize) -> Option<Self> {
+        if record_len < 8 {
+            return None;
+        }
+        let word =
+            |record: &[u8], offset: usize| u16::from_be_bytes([record[offset], record[offset + 1]]);
+        let mut info = RetentiveInfo::default();
+        let mut marker_bits = None;
+        let mut marker_bytes = None;
+        for record in data.chunks_exact(record_len).take(record_count) {
+            // 记录: 索引、存储器类型、数量、保持性元素数量
+            let reman = word(record, 6);
+            match word(record, 0) {
+                0x0003 => marker_bits = Some(reman as usize),
+                0x0004 => info.timers = 0..reman,
+                0x0005 => info.counters = 0..reman,
+                0x0008 => marker_bytes = Some(reman as usize),
+                _ => {}
+            }
+        }
+        info.marker_bytes = 0..marker_bytes.or(marker_bits.map(|bits| bits / 8))?;
+        Some(info)
+    }
+}
+
+/// 服务端运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServerState {
+    /// 服务停止
+    Stopped = 0,
+    /// 服务运行
+    Running = 1,
+    /// 服务错误
+    Error = 2,
+}
+
+impl ServerState {
+    ///
+    /// 将 get_status() 返回的原始服务端状态转换为 ServerState。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - code: 原始服务端状态
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 对应的 ServerState
+    ///  - None: 无效的状态代码
+    ///
+    pub fn from_raw(code: c_int) -> Option<ServerState> {
+        match code {
+            0 => Some(ServerState::Stopped),
+            1 => Some(ServerState::Running),
+            2 => Some(ServerState::Error),
+            _ => None,
+        }
+    }
+}
+
+/// CPU 运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CpuStatus {
+    /// 状态未知
+    Unknown = S7_CPU_STATUS_UNKNOWN as isize,
+    /// CPU Run
+    Run = S7_CPU_STATUS_RUN as isize,
+    /// CPU Stop
+    Stop = S7_CPU_STATUS_STOP as isize,
+}
+
+impl CpuStatus {
+    ///
+    /// 将原始 CPU 状态代码转换为 CpuStatus，无法识别的代码视为 Unknown。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - code: 原始 CPU 状态代码
+    ///
+    /// **返回值:**
+    ///
+    ///  - 对应的 CpuStatus
+    ///
+    pub fn from_raw(code: c_int) -> CpuStatus {
+        match code {
+            S7_CPU_STATUS_RUN => CpuStatus::Run,
+            S7_CPU_STATUS_STOP => CpuStatus::Stop,
+            _ => CpuStatus::Unknown,
+        }
+    }
+
+    ///
+    /// 由诊断缓冲区(SZL 0x00A0)的事件 ID 推断 CPU 切换后的运行状态。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - event_id: 诊断事件 ID
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 切换至 RUN(0x4302)或 STOP(0x4303、0x4304 等及 0x45xx)
+    ///  - None: 不是进入 RUN 或 STOP 的模式切换事件
+    ///
+    pub fn from_diag_event(event_id: u16) -> Option<CpuStatus> {
+        match event_id {
+            0x4302 => Some(CpuStatus::Run),
+            0x4303 | 0x4304 | 0x430A | 0x430D | 0x430F => Some(CpuStatus::Stop),
+            0x4500..=0x45FF => Some(CpuStatus::Stop),
+            _ => None,
+        }
+    }
+}
+
+/// 服务端状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServerStatus {
+    /// 服务端运行状态
+    pub server: ServerState,
+    /// 虚拟 CPU 状态
+    pub cpu: CpuStatus,
+    /// 已连接的客户端数量
+    pub clients: i32,
+}
+
+/// 服务端事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ServerEvent {
+    /// 事件时间戳(Unix 时间，秒)
+    pub time: i64,
+    /// 发送者(客户端 IP 地址)
+    pub sender: i32,
+    /// 事件代码，见 EVC_* 常量
+    pub code: u32,
+    /// 事件结果
+    pub ret_code: u16,
+    /// 事件参数 1~4
+    pub params: [u16; 4],
+}
+
+impl From<TSrvEvent> for ServerEvent {
+    fn from(event: TSrvEvent) -> Self {
+        ServerEvent {
+            time: event.EvtTime as i64,
+            sender: event.EvtSender,
+            code: event.EvtCode,
+            ret_code: event.EvtRetCode,
+            params: [
+                event.EvtParam1,
+                event.EvtParam2,
+                event.EvtParam3,
+                event.EvtParam4,
+            ],
+        }
+    }
+}
+
+impl From<ServerEvent> for TSrvEvent {
+    fn from(event: ServerEvent) -> Self {
+        TSrvEvent {
+            EvtTime: event.time as _,
+            EvtSender: event.sender,
+            EvtCode: event.code,
+            EvtRetCode: event.ret_code,
+            EvtParam1: event.params[0],
+            EvtParam2: event.params[1],
+            EvtParam3: event.params[2],
+            EvtParam4: event.params[3],
+        }
+    }
+}
+
+/// 事件队列已满时的处理策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueFullPolicy {
+    /// 丢弃新事件，snap7 线程立即返回
+    Drop,
+    /// 阻塞 snap7 线程直到队列有空位
+    Block,
+}
+
+/// db_directory() 返回的数据块(DB)信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DbEntry {
+    /// DB 编号
+    pub number: u16,
+    /// DB 大小(字节，即 MC7Size)
+    pub size: u32,
+    /// 是否为"优化的块访问"
+    pub optimized: bool,
+}
+
+/// snap7 异步任务的操作类型(s7_micro_client.h 中的 s7opXXX)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AsyncOp {
+    ReadArea = 1,
+    WriteArea = 2,
+    ReadMultiVars = 3,
+    WriteMultiVars = 4,
+    DbGet = 5,
+    Upload = 6,
+    Download = 7,
+    Delete = 8,
+    ListBlocks = 9,
+    AgBlockInfo = 10,
+    ListBlocksOfType = 11,
+    ReadSzlList = 12,
+    ReadSzl = 13,
+    GetDateTime = 14,
+    SetDateTime = 15,
+    GetOrderCode = 16,
+    GetCpuInfo = 17,
+    GetCpInfo = 18,
+    GetPlcStatus = 19,
+    PlcHotStart = 20,
+    PlcColdStart = 21,
+    CopyRamToRom = 22,
+    Compress = 23,
+    PlcStop = 24,
+    GetProtection = 25,
+    SetPassword = 26,
+    ClearPassword = 27,
+    DbFill = 28,
+}
+
+impl AsyncOp {
+    ///
+    /// 将异步回调中的原始操作码转换为 AsyncOp。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - code: 操作码
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 对应的操作类型
+    ///  - None: 0(无操作)或无法识别的操作码
+    ///
+    pub fn from_code(code: i32) -> Option<AsyncOp> {
+        use AsyncOp::*;
+        const OPS: [AsyncOp; 28] = [
+            ReadArea,
+            WriteArea,
+            ReadMultiVars,
+            WriteMultiVars,
+            DbGet,
+            Upload,
+            Download,
+            Delete,
+            ListBlocks,
+            AgBlockInfo,
+            ListBlocksOfType,
+            ReadSzlList,
+            ReadSzl,
+            GetDateTime,
+            SetDateTime,
+            GetOrderCode,
+            GetCpuInfo,
+            GetCpInfo,
+            GetPlcStatus,
+            PlcHotStart,
+            PlcColdStart,
+            CopyRamToRom,
+            Compress,
+            PlcStop,
+            GetProtection,
+            SetPassword,
+            ClearPassword,
+            DbFill,
+        ];
+        OPS.into_iter().find(|op| *op as i32 == code)
+    }
+}
+
+/// 连接层错误的重试策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 首次失败后的最大重试次数
+    pub max_retries: u32,
+    /// 每次重新连接前的等待时间
+    pub backoff: std::time::Duration,
+}
+
+impl Default for RetryPolicy {
+    /// 最多重试 3 次，每次等待 500ms
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            backoff: std::time::Duration::from_millis(500),
+        }
+    }
+}
+
+/// 客户端请求的 CPU 控制操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlAction {
+    /// 暖启动(热启动)
+    HotStart,
+    /// 冷启动
+    ColdStart,
+    /// 停止
+    Stop,
+    /// 内存压缩
+    Compress,
+    /// 复制 RAM 到 ROM
+    CopyRamToRom,
+    /// 插入/删除块
+    InsertDelete,
+    /// 未知的控制操作
+    Unknown,
+}
+
+/// EVC_CONTROL 事件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ControlEvent {
+    /// 控制操作
+    pub action: ControlAction,
+}
+
+/// 服务端拒绝客户端连接的原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    /// 已达到 MaxClients 设置的最大连接数(EVC_CLIENT_REJECTED)
+    MaxClientsReached,
+    /// 服务端没有空闲的工作线程槽位(EVC_CLIENT_NO_ROOM)
+    NoRoom,
+}
+
+impl ServerEvent {
+    ///
+    /// 解析客户端被拒绝的原因。
+    ///
+    /// snap7 的拒绝事件不携带返回码(ret_code 总为 0)，原因由事件代码区分。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 拒绝原因，sender 为被拒绝客户端的 IP 地址
+    ///  - None: 不是拒绝事件
+    ///
+    pub fn rejection_reason(&self) -> Option<RejectionReason> {
+        match self.code {
+            EVC_CLIENT_REJECTED => Some(RejectionReason::MaxClientsReached),
+            EVC_CLIENT_NO_ROOM => Some(RejectionReason::NoRoom),
+            _ => None,
+        }
+    }
+
+    ///
+    /// 解析 EVC_CONTROL 事件中客户端请求的控制操作(启动、停止等)。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Some: 控制事件，sender 为发起请求的客户端 IP 地址
+    ///  - None: 不是控制事件
+    ///
+    pub fn control_event(&self) -> Option<ControlEvent> {
+        if self.code != EVC_CONTROL {
+            return None;
+        }
+        let action = match self.params[0] {
+            CodeControlWarmStart => ControlAction::HotStart,
+            CodeControlColdStart => ControlAction::ColdStart,
+            CodeControlStop => ControlAction::Stop,
+            CodeControlCompress => ControlAction::Compress,
+            CodeControlCpyRamRom => ControlAction::CopyRamToRom,
+            CodeControlInsDel => ControlAction::InsertDelete,
+            _ => ControlAction::Unknown,
+        };
+        Some(ControlEvent { action })
+    }
+}
+
+/// iso_exchange() 返回的 S7 应答 PDU
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct S7Response {
+    /// 报文类型(ROSCTR)
+    ///  - 0x02: Ack
+    ///  - 0x03: Ack_Data
+    ///  - 0x07: Userdata
+    pub rosctr: u8,
+    /// PDU 引用号，与请求一致
+    pub pdu_ref: u16,
+    /// 错误类别，0 为无错误(Userdata 报文没有该字段，为 0)
+    pub error_class: u8,
+    /// 错误代码
+    pub error_code: u8,
+    /// 功能码(参数区首字节)，如 0x04 读变量、0x05 写变量、0xF0 建立通讯，参数区为空时为 0
+    pub function: u8,
+    /// 参数区
+    pub parameters: Vec<u8>,
+    /// 数据区
+    pub data: Vec<u8>,
+}
+
+impl S7Response {
+    ///
+    /// 解析 S7 应答 PDU(以协议号 0x32 开头，不含 TPKT/COTP 头)。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - pdu: 应答 PDU
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: S7Response 结构体
+    ///  - Err: 不是 S7 PDU 或长度与报文头不符
+    ///
+    pub fn parse(pdu: &[u8]) -> std::result::Result<Self, String> {
+        if pdu.len() < 10 || pdu[0] != 0x32 {
+            return Err("not an S7 PDU".to_string());
+        }
+        let rosctr = pdu[1];
+        // Ack 与 Ack_Data 的报文头多出错误类别和错误代码两个字节
+        let header_len = if matches!(rosctr, 0x02 | 0x03) {
+            12
+        } else {
+            10
+        };
+        if pdu.len() < header_len {
+            return Err(format!("S7 PDU header too short: {} bytes", pdu.len()));
+        }
+        let param_len = u16::from_be_bytes([pdu[6], pdu[7]]) as usize;
+        let data_len = u16::from_be_bytes([pdu[8], pdu[9]]) as usize;
+        let end = header_len + param_len + data_len;
+        if pdu.len() < end {
+            return Err(format!(
+                "S7 PDU is {} bytes but its header declares {}",
+                pdu.len(),
+                end
+            ));
+        }
+        let (error_class, error_code) = match header_len {
+            12 => (pdu[10], pdu[11]),
+            _ => (0, 0),
+        };
+        let parameters = pdu[header_len..header_len + param_len].to_vec();
+        Ok(S7Response {
+            rosctr,
+            pdu_ref: u16::from_be_bytes([pdu[4], pdu[5]]),
+            error_class,
+            error_code,
+            function: parameters.first().copied().unwrap_or(0),
+            parameters,
+            data: pdu[header_len + param_len..end].to_vec(),
+        })
+    }
+
+    /// 报文头中没有错误
+    pub fn is_ok(&self) -> bool {
+        self.error_class == 0 && self.error_code == 0
+    }
+}
+
+/// CPU 保护状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ProtectionState {
+    /// CPU 当前有效的保护级别
+    ///  - 1: 无保护
+    ///  - 2: 写保护
+    ///  - 3: 读写保护
+    pub level: u16,
+    /// 组态的保护级别(模式开关与参数中较高者)
+    pub configured_level: u16,
+    /// 读取是否需要密码
+    pub read_password_required: bool,
+    /// 写入是否需要密码
+    pub write_password_required: bool,
+    /// 当前会话是否已通过密码验证
+    pub authenticated: bool,
+    /// 模式开关位置
+    ///  - 0: 未定义
+    ///  - 1: RUN
+    ///  - 2: RUN-P
+    ///  - 3: STOP
+    ///  - 4: MRES
+    pub mode_selector: u16,
+}
+
+impl From<TS7Protection> for ProtectionState {
+    fn from(protection: TS7Protection) -> Self {
+        let level = protection.sch_rel;
+        let configured_level = protection.sch_schal.max(protection.sch_par);
+        ProtectionState {
+            level,
+            configured_level,
+            read_password_required: level >= 3,
+            write_password_required: level >= 2,
+            authenticated: level < configured_level,
+            mode_selector: protection.bart_sch,
+        }
+    }
+}
+
+impl ProtectionState {
+    ///
+    /// 返回保护状态的单行文字摘要，便于记录日志。
+    ///
+    pub fn summary(&self) -> String {
+        let level = match self.level {
+            1 => "no protection",
+            2 => "write protection",
+            3 => "read/write protection",
+            _ => "unknown protection",
+        };
+        let password = if self.configured_level > 1 {
+            "password set"
+        } else {
+            "no password"
+        };
+        let mode = match self.mode_selector {
+            1 => "RUN",
+            2 => "RUN-P",
+            3 => "STOP",
+            4 => "MRES",
+            _ => "undefined",
+        };
+        format!(
+            "{} (level {}/{}), {}{}, mode selector {}",
+            level,
+            self.level,
+            self.configured_level,
+            password,
+            if self.authenticated {
+                ", authenticated"
+            } else {
+                ""
+            },
+            mode
+        )
+    }
+}
+
+///
+/// 返回一个 SZL ID 的文字说明。
+///
+/// 先匹配常用的具体 ID，其次按部分列表编号(ID 低字节)匹配，未知的 ID 返回 None。
+///
+/// **输入参数:**
+///
+///  - id: SZL ID
+///
+pub fn szl_description(id: u16) -> Option<&'static str> {
+    let desc = match id {
+        0x0000 => "List of all SZL IDs",
+        0x0111 => "Module identification: single identification data record",
+        0x011C => "Component identification: single component",
+        0x0131 => "Communication capability parameters",
+        0x0132 => "Communication status data",
+        0x0232 => "Protection level and operating mode switch setting",
+        0x0424 => "Current operating mode",
+        _ => match id & 0x00FF {
+            0x11 => "Module identification",
+            0x12 => "CPU characteristics",
+            0x13 => "User memory areas",
+            0x14 => "System areas",
+            0x15 => "Block types",
+            0x17 => "Status of system data blocks (SDB)",
+            0x18 => "Maximum S7 configuration",
+            0x19 => "Status of the module LEDs",
+            0x1A => "Load memory information",
+            0x1B => "Startup information",
+            0x1C => "Component identification",
+            0x21 => "Interrupt assignment",
+            0x22 => "Interrupt status",
+            0x23 => "Priority classes",
+            0x24 => "Operating mode transitions",
+            0x25 => "Process image partitions",
+            0x31 => "Communication capability parameters",
+            0x32 => "Communication status data",
+            0x36 => "Module diagnostic information",
+            0x37 => "Ethernet details of the module",
+            0x38 => "Status of the TCP/IP stack",
+            0x39 => "Communication connections",
+            0x3A => "Communication resources",
+            0x74 => "Status of the module LEDs",
+            0x90 => "DP master system information",
+            0x91 => "Module status information",
+            0x92 => "Rack/station status information",
+            0x94 => "Rack/station status information (PROFINET)",
+            0x95 => "Extended DP master system/PROFINET IO system information",
+            0x96 => "Module status information (PROFINET IO/PROFIBUS DP)",
+            0x97 => "Tool changer information",
+            0x9A => "PROFINET IO system information",
+            0x9B => "PROFINET IO system status",
+            0x9C => "Tool changer information (PROFINET IO)",
+            0xA0 => "Diagnostic buffer",
+            0xB1 => "Module diagnostic data (data record 0)",
+            0xB2 => "Module diagnostic data (data record 1, geographical address)",
+            0xB3 => "Module diagnostic data (data record 1, logical address)",
+            0xB4 => "Diagnostic data of a DP slave",
+            0xB5 => "Diagnostic data of a PROFINET IO device",
+            _ => return None,
+        },
+    };
+    Some(desc)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_s7_response_parse() {
+        // 读 DB1.DBB0 4 字节的应答
+        let pdu = [
+            0x32, 0x03, 0x00, 0x00, 0x00, 0x01, 0x00, 0x02, 0x00, 0x08, 0x00, 0x00, 0x04, 0x01,
+            0xFF, 0x04, 0x00, 0x20, 0xDE, 0xAD, 0xBE, 0xEF,
+        ];
+        let response = S7Response::parse(&pdu).unwrap();
+        assert_eq!(
+            response,
+            S7Response {
+                rosctr: 0x03,
+                pdu_ref: 1,
+                error_class: 0,
+                error_code: 0,
+                function: 0x04,
+                parameters: vec![0x04, 0x01],
+                data: vec![0xFF, 0x04, 0x00, 0x20, 0xDE, 0xAD, 0xBE, 0xEF],
+            }
+        );
+        assert!(response.is_ok());
+
+        // 功能不支持: 错误类别 0x81，错误代码 0x04
+        let pdu = [
+            0x32, 0x02, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x81, 0x04,
+        ];
+        let response = S7Response::parse(&pdu).unwrap();
+        assert_eq!((response.error_class, response.error_code), (0x81, 0x04));
+        assert!(!response.is_ok());
+
+        assert!(S7Response::parse(&pdu[..11]).is_err());
+        assert!(S7Response::parse(&[0x03, 0x00, 0x00, 0x16]).is_err());
+    }
+
+    #[test]
+    fn test_tsap() {
+        assert_eq!(Tsap::new(0x10, 0, 2).value(), 0x1002);
+        assert_eq!(Tsap::new(0x01, 0, 2).value(), 0x0102);
+        assert_eq!(Tsap::new(0x03, 1, 3).value(), 0x0323);
+        assert_eq!(u16::from(Tsap::new(0x02, 7, 31)), 0x02FF);
+
+        let tsap = Tsap::from(0x0323);
+        assert_eq!(
+            (tsap.connection_resource(), tsap.rack(), tsap.slot()),
+            (0x03, 1, 3)
+        );
+    }
+
+    #[test]
+    fn test_area_names() {
+        let names = [
+            (AreaCode::S7AreaPE, AreaTable::S7AreaPE, "Inputs"),
+            (AreaCode::S7AreaPA, AreaTable::S7AreaPA, "Outputs"),
+            (AreaCode::S7AreaMK, AreaTable::S7AreaMK, "Merkers"),
+            (AreaCode::S7AreaCT, AreaTable::S7AreaCT, "Counters"),
+            (AreaCode::S7AreaTM, AreaTable::S7AreaTM, "Timers"),
+            (AreaCode::S7AreaDB, AreaTable::S7AreaDB, "DB"),
+        ];
+        for (code, table, name) in names {
+            assert_eq!(code.name(), name);
+            assert_eq!(table.name(), name);
+            assert_eq!(code.to_string(), name);
+            assert_eq!(format!("{}", table), name);
+        }
+    }
+
+    #[test]
+    fn test_async_op_from_code() {
+        assert_eq!(AsyncOp::from_code(1), Some(AsyncOp::ReadArea));
+        assert_eq!(AsyncOp::from_code(6), Some(AsyncOp::Upload));
+        assert_eq!(AsyncOp::from_code(28), Some(AsyncOp::DbFill));
+        assert_eq!(AsyncOp::from_code(0), None);
+        assert_eq!(AsyncOp::from_code(29), None);
+        for code in 1..=28 {
+            assert_eq!(AsyncOp::from_code(code).unwrap() as i32, code);
+        }
+    }
+
+    #[test]
+    fn test_conn_type_resource() {
+        assert_eq!(ConnType::PG.resource(), 0x01);
+        assert_eq!(ConnType::OP.resource(), 0x02);
+        assert_eq!(ConnType::S7Basic(0x0310).resource(), 0x0310);
+        for conn_type in [ConnType::PG, ConnType::OP, ConnType::S7Basic(0x0310)] {
+            assert_eq!(ConnType::from_resource(conn_type.resource()), conn_type);
+        }
+        assert_eq!(ConnType::from_resource(0x03), ConnType::S7Basic(0x03));
+    }
+
+    #[test]
+    fn test_rw_request_db_number() {
+        let mut tag = TS7Tag {
+            Area: AreaTable::S7AreaMK as c_int,
+            DBNumber: 5,
+            Start: 0,
+            Size: 2,
+            WordLen: WordLenTable::S7WLByte as c_int,
+        };
+        let request = RwRequest::from_tag(1, &tag);
+        assert_eq!(request.area, Some(AreaTable::S7AreaMK));
+        assert_eq!(request.db_number, None);
+
+        tag.Area = AreaTable::S7AreaDB as c_int;
+        assert_eq!(RwRequest::from_tag(1, &tag).db_number, Some(5));
+    }
+
+    #[test]
+    fn test_szl_description() {
+        assert_eq!(
+            szl_description(0x0232),
+            Some("Protection level and operating mode switch setting")
+        );
+        assert_eq!(szl_description(0x0011), Some("Module identification"));
+        assert_eq!(szl_description(0x0F12), Some("CPU characteristics"));
+        assert_eq!(szl_description(0x00A0), Some("Diagnostic buffer"));
+        assert_eq!(szl_description(0x00FF), None);
+    }
+
+    #[test]
+    fn test_area_table_from_raw() {
+        assert_eq!(AreaTable::from_raw(0x81), Some(AreaTable::S7AreaPE));
+        assert_eq!(AreaTable::from_raw(0x82), Some(AreaTable::S7AreaPA));
+        assert_eq!(AreaTable::from_raw(0x83), Some(AreaTable::S7AreaMK));
+        assert_eq!(AreaTable::from_raw(0x84), Some(AreaTable::S7AreaDB));
+        assert_eq!(AreaTable::from_raw(0x1c), Some(AreaTable::S7AreaCT));
+        assert_eq!(AreaTable::from_raw(0x1d), Some(AreaTable::S7AreaTM));
+        assert_eq!(AreaTable::from_raw(0x85), None);
+    }
+
+    #[test]
+    fn test_area_code_from_raw() {
+        assert_eq!(AreaCode::from_raw(0), Some(AreaCode::S7AreaPE));
+        assert_eq!(AreaCode::from_raw(1), Some(AreaCode::S7AreaPA));
+        assert_eq!(AreaCode::from_raw(2), Some(AreaCode::S7AreaMK));
+        assert_eq!(AreaCode::from_raw(3), Some(AreaCode::S7AreaCT));
+        assert_eq!(AreaCode::from_raw(4), Some(AreaCode::S7AreaTM));
+        assert_eq!(AreaCode::from_raw(5), Some(AreaCode::S7AreaDB));
+        assert_eq!(AreaCode::from_raw(6), None);
+    }
+
+    #[test]
+    fn test_word_len_table_from_raw() {
+        assert_eq!(WordLenTable::from_raw(0x01), Some(WordLenTable::S7WLBit));
+        assert_eq!(WordLenTable::from_raw(0x02), Some(WordLenTable::S7WLByte));
+        assert_eq!(WordLenTable::from_raw(0x04), Some(WordLenTable::S7WLWord));
+        assert_eq!(WordLenTable::from_raw(0x06), Some(WordLenTable::S7WLDWord));
+        assert_eq!(WordLenTable::from_raw(0x08), Some(WordLenTable::S7WLReal));
+        assert_eq!(
+            WordLenTable::from_raw(0x1c),
+            Some(WordLenTable::S7WLCounter)
+        );
+        assert_eq!(WordLenTable::from_raw(0x1d), Some(WordLenTable::S7WLTimer));
+        assert_eq!(WordLenTable::from_raw(0x03), None);
+    }
+
+    #[test]
+    fn test_required_buffer_size() {
+        // 定时器和计数器区域按 amount*2，与 word_len 无关
+        assert_eq!(
+            required_buffer_size(AreaTable::S7AreaTM, WordLenTable::S7WLTimer, 5),
+            10
+        );
+        assert_eq!(
+            required_buffer_size(AreaTable::S7AreaCT, WordLenTable::S7WLByte, 3),
+            6
+        );
+        assert_eq!(
+            required_buffer_size(AreaTable::S7AreaDB, WordLenTable::S7WLBit, 1),
+            1
+        );
+        assert_eq!(
+            required_buffer_size(AreaTable::S7AreaMK, WordLenTable::S7WLByte, 16),
+            16
+        );
+        assert_eq!(
+            required_buffer_size(AreaTable::S7AreaDB, WordLenTable::S7WLReal, 4),
+            16
+        );
+    }
+
+    #[test]
+    fn test_protection_summary() {
+        let state = ProtectionState::from(TS7Protection {
+            sch_schal: 1,
+            sch_par: 3,
+            sch_rel: 3,
+            bart_sch: 1,
+            anl_sch: 0,
+        });
+        assert_eq!(
+            state.summary(),
+            "read/write protection (level 3/3), password set, mode selector RUN"
+        );
+
+        let state = ProtectionState::from(TS7Protection {
+            sch_schal: 1,
+            sch_par: 0,
+            sch_rel: 1,
+            bart_sch: 2,
+            anl_sch: 0,
+        });
+        assert_eq!(
+            state.summary(),
+            "no protection (level 1/1), no password, mode selector RUN-P"
+        );
+    }
+}
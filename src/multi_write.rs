@@ -0,0 +1,228 @@
+//
+// multi_write.rs
+// Copyright (C) 2021 gmg137 <gmg137 AT live.com>
+// snap7-rs is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//          http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+// EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+// MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+//
+use crate::{
+    client::S7Client,
+    error::Snap7Error,
+    ffi::{MaxVars, TS7DataItem},
+    model::*,
+    utils::{address::TagAddress, layout::S7Type, setters},
+};
+use anyhow::*;
+use std::os::raw::{c_int, c_void};
+
+/// 带类型的批量写入构建器
+///
+/// 按地址累积编码后的值，commit() 时以尽可能少的 write_multi_vars() 调用写入，
+/// 每次调用最多 20 项且不超过协商的 PDU 长度。
+///
+/// # Examples
+/// ```ignore
+/// use rust_snap7::MultiVarWrite;
+///
+/// MultiVarWrite::new()
+///     .set_real("DB1.DBD0".parse()?, 21.5)
+///     .set_int("DB1.DBW4".parse()?, -7)
+///     .set_bool("M10.3".parse()?, true)
+///     .commit(&client)?;
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MultiVarWrite {
+    items: Vec<WriteItem>,
+    /// 第一个无效的地址，commit() 时报告
+    invalid: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+struct WriteItem {
+    address: TagAddress,
+    word_len: WordLenTable,
+    start: i32,
+    data: Vec<u8>,
+}
+
+impl MultiVarWrite {
+    /// 创建空的批量写入
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 待写入的项数
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// 是否没有待写入的项
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// 在地址处写入 REAL，地址必须为 4 字节宽(如 `DB1.DBD0`、`MD4`)
+    pub fn set_real(self, address: TagAddress, value: f32) -> Self {
+        let mut data = [0u8; 4];
+        setters::set_real(&mut data, 0, value);
+        self.push_bytes(address, &data)
+    }
+
+    /// 在地址处写入 INT，地址必须为 2 字节宽(如 `DB1.DBW4`、`MW2`)
+    pub fn set_int(self, address: TagAddress, value: i16) -> Self {
+        let mut data = [0u8; 2];
+        setters::set_int(&mut data, 0, value);
+        self.push_bytes(address, &data)
+    }
+
+    /// 写入单个位，地址必须带位索引(如 `DB1.DBX0.1`、`M10.3`)
+    pub fn set_bool(mut self, address: TagAddress, value: bool) -> Self {
+        match address.s7_type {
+            S7Type::Bool(bit) => self.items.push(WriteItem {
+                address,
+                word_len: WordLenTable::S7WLBit,
+                start: (address.byte_offset * 8 + bit) as i32,
+                data: vec![value as u8],
+            }),
+            _ => {
+                self.invalid
+                    .get_or_insert_with(|| format!("{:?} is not a bit address", address));
+            }
+        }
+        self
+    }
+
+    fn push_bytes(mut self, address: TagAddress, data: &[u8]) -> Self {
+        let width_matches = match address.s7_type {
+            S7Type::Bool(_) | S7Type::String(_) => false,
+            s7_type => s7_type.size() == data.len(),
+        };
+        if !width_matches {
+            self.invalid.get_or_insert_with(|| {
+                format!("{:?} is not a {}-byte address", address, data.len())
+            });
+            return self;
+        }
+        self.items.push(WriteItem {
+            address,
+            word_len: WordLenTable::S7WLByte,
+            start: address.byte_offset as i32,
+            data: data.to_vec(),
+        });
+        self
+    }
+
+    ///
+    /// 写入所有累积的值。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - client: 已连接的客户端
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 全部写入成功
+    ///  - Err: 存在无效地址(此时不写入任何值)，或某项写入失败(之前的分组已写入)
+    ///
+    pub fn commit(&self, client: &S7Client) -> Result<()> {
+        if let Some(invalid) = &self.invalid {
+            bail!("{}", invalid);
+        }
+        let (mut requested, mut negotiated) = (0, 0);
+        client.get_pdu_length(&mut requested, &mut negotiated)?;
+        let pdu = if negotiated > 0 { negotiated } else { 240 };
+        // 写请求: 10 字节头 + 2 字节参数，每项 12 字节参数和 4 字节数据头，数据按偶数对齐
+        let budget = (pdu as usize).saturating_sub(12);
+
+        let mut group: Vec<&WriteItem> = Vec::new();
+        let mut used = 0;
+        for item in &self.items {
+            let cost = 16 + item.data.len().div_ceil(2) * 2;
+            if !group.is_empty() && (group.len() == MaxVars as usize || used + cost > budget) {
+                Self::write_group(client, &group)?;
+                group.clear();
+                used = 0;
+            }
+            group.push(item);
+            used += cost;
+        }
+        if !group.is_empty() {
+            Self::write_group(client, &group)?;
+        }
+        Ok(())
+    }
+
+    fn write_group(client: &S7Client, group: &[&WriteItem]) -> Result<()> {
+        // 数据缓冲区在 write_multi_vars() 返回前保持有效
+        let mut buffers: Vec<Vec<u8>> = group.iter().map(|item| item.data.clone()).collect();
+        let mut data_items: Vec<TS7DataItem> = group
+            .iter()
+            .zip(buffers.iter_mut())
+            .map(|(item, buff)| TS7DataItem {
+                Area: item.address.area as c_int,
+                WordLen: item.word_len as c_int,
+                Result: 0,
+                DBNumber: item.address.db_number as c_int,
+                Start: item.start as c_int,
+                Amount: buff.len() as c_int,
+                pdata: buff.as_mut_ptr() as *mut c_void,
+            })
+            .collect();
+        client.write_multi_vars(&mut data_items, group.len() as i32)?;
+        for (item, data_item) in group.iter().zip(&data_items) {
+            if data_item.Result != 0 {
+                return Err(Error::new(Snap7Error::client(data_item.Result)))
+                    .with_context(|| format!("writing {:?}", item.address));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::*, utils::getters};
+
+    #[test]
+    fn test_commit() {
+        let mut db_buff = [0u8; 8];
+        let mut mk_buff = [0u8; 4];
+        let (server, port) = start_server(&mut [
+            (AreaCode::S7AreaDB, 1, &mut db_buff),
+            (AreaCode::S7AreaMK, 0, &mut mk_buff),
+        ]);
+        let client = connect_client(port);
+
+        let write = MultiVarWrite::new()
+            .set_real("DB1.DBD0".parse().unwrap(), 21.5)
+            .set_int("DB1.DBW4".parse().unwrap(), -7)
+            .set_bool("DB1.DBX6.2".parse().unwrap(), true)
+            .set_bool("M1.7".parse().unwrap(), true);
+        assert_eq!(write.len(), 4);
+        write.commit(&client).unwrap();
+
+        let invalid = MultiVarWrite::new()
+            .set_int("DB1.DBW4".parse().unwrap(), 1)
+            .set_bool("DB1.DBB6".parse().unwrap(), true);
+        assert!(invalid.commit(&client).is_err());
+        let invalid = MultiVarWrite::new().set_real("DB1.DBW4".parse().unwrap(), 1.0);
+        assert!(invalid.commit(&client).is_err());
+        let invalid = MultiVarWrite::new().set_int("DB1.DBD0".parse().unwrap(), 1);
+        assert!(invalid.commit(&client).is_err());
+        let invalid = MultiVarWrite::new().set_int("DB1.DBX6.2".parse().unwrap(), 1);
+        assert!(invalid.commit(&client).is_err());
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+        assert_eq!(getters::get_real(&db_buff, 0), 21.5);
+        assert_eq!(getters::get_int(&db_buff, 4), -7);
+        assert_eq!(db_buff[6], 0b0000_0100);
+        assert_eq!(mk_buff[1], 0b1000_0000);
+    }
+}
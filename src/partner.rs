@@ -10,19 +10,24 @@
 // MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
 // See the Mulan PSL v2 for more details.
 //
-use crate::{ffi::*, model::*};
+use crate::{error::*, ffi::*, model::*};
 use anyhow::*;
 use std::{
     ffi::{CStr, CString},
     os::raw::*,
+    sync::atomic::{AtomicU16, AtomicU32, Ordering},
+    time::Duration,
 };
 
+/// 单个数据包的最大长度(snap7 MaxBSendSize)
+const MAX_BSEND_SIZE: usize = 0x10000;
+
 /// S7 伙伴
 ///
 /// # Examples
 /// 创建被动伙伴
 /// ```ignore
-/// use snap7_rs::S7Partner;
+/// use rust_snap7::S7Partner;
 /// use std::ffi::*;
 /// use std::os::raw::*;
 ///
@@ -53,7 +58,7 @@ use std::{
 ///
 /// 创建主动伙伴
 /// ```ignore
-/// use snap7_rs::S7Partner;
+/// use rust_snap7::S7Partner;
 /// use std::ffi::*;
 /// use std::os::raw::*;
 ///
@@ -98,6 +103,18 @@ use std::{
 /// ```
 pub struct S7Partner {
     handle: usize,
+    remote_tsap: AtomicU16,
+    default_r_id: AtomicU32,
+}
+
+impl ParamAccess for S7Partner {
+    fn get_param(&self, param: InternalParam, value: &mut InternalParamValue) -> Result<()> {
+        S7Partner::get_param(self, param, value)
+    }
+
+    fn set_param(&self, param: InternalParam, value: InternalParamValue) -> Result<()> {
+        S7Partner::set_param(self, param, value)
+    }
 }
 
 impl Drop for S7Partner {
@@ -120,6 +137,8 @@ impl S7Partner {
     pub fn create(active: i32) -> Self {
         S7Partner {
             handle: unsafe { Par_Create(active as c_int) },
+            remote_tsap: AtomicU16::new(0),
+            default_r_id: AtomicU32::new(0),
         }
     }
 
@@ -149,7 +168,7 @@ impl S7Partner {
                     *value = InternalParamValue::U32(u32::from_le_bytes(buff));
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::partner(res))
             },
             InternalParam::LocalPort
             | InternalParam::RemotePort
@@ -166,7 +185,7 @@ impl S7Partner {
                     *value = InternalParamValue::U16(u16::from_le_bytes(buff));
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::partner(res))
             },
             _ => unsafe {
                 let mut buff = [0u8; 4];
@@ -179,7 +198,7 @@ impl S7Partner {
                     *value = InternalParamValue::I32(i32::from_le_bytes(buff));
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::partner(res))
             },
         }
     }
@@ -210,7 +229,7 @@ impl S7Partner {
                     if res == 0 {
                         return Ok(());
                     }
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::partner(res))
                 } else {
                     bail!("{}", Self::error_text(-1))
                 }
@@ -230,7 +249,7 @@ impl S7Partner {
                     if res == 0 {
                         return Ok(());
                     }
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::partner(res))
                 } else {
                     bail!("{}", Self::error_text(-1))
                 }
@@ -246,7 +265,7 @@ impl S7Partner {
                     if res == 0 {
                         return Ok(());
                     }
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::partner(res))
                 } else {
                     bail!("{}", Self::error_text(-1))
                 }
@@ -254,6 +273,86 @@ impl S7Partner {
         }
     }
 
+    ///
+    /// 读取伙伴支持的所有内部参数，便于诊断输出当前配置。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: PartnerParams 结构体
+    ///  - Err: 读取某个参数失败
+    ///
+    pub fn params(&self) -> Result<PartnerParams> {
+        use InternalParamValue as V;
+        Ok(PartnerParams {
+            local_port: self.param(InternalParam::LocalPort, V::as_u16)?,
+            remote_port: self.param(InternalParam::RemotePort, V::as_u16)?,
+            ping_timeout: self.param(InternalParam::PingTimeout, V::as_millis)?,
+            send_timeout: self.param(InternalParam::SendTimeout, V::as_millis)?,
+            recv_timeout: self.param(InternalParam::RecvTimeout, V::as_millis)?,
+            work_interval: self.param(InternalParam::WorkInterval, V::as_millis)?,
+            src_ref: self.param(InternalParam::SrcRef, V::as_u16)?,
+            dst_ref: self.param(InternalParam::DstRef, V::as_u16)?,
+            src_tsap: self.param(InternalParam::SrcTSap, V::as_u16)?,
+            pdu_request: self.param(InternalParam::PDURequest, V::as_i32)?,
+            bsend_timeout: self.param(InternalParam::BSendTimeout, V::as_millis)?,
+            brecv_timeout: self.param(InternalParam::BRecvTimeout, V::as_millis)?,
+            recovery_time: self.param(InternalParam::RecoveryTime, V::as_millis)?,
+            keep_alive_time: self.param(InternalParam::KeepAliveTime, V::as_millis)?,
+        })
+    }
+
+    ///
+    /// 读取 BSend 超时时间。
+    ///
+    /// **返回值:**
+    ///  - Ok: 超时时间
+    ///  - Err: 操作失败
+    ///
+    pub fn bsend_timeout(&self) -> Result<Duration> {
+        self.param(InternalParam::BSendTimeout, InternalParamValue::as_millis)
+    }
+
+    ///
+    /// 设置 BSend 超时时间，即等待对方确认一个数据包的最长时间。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - timeout: 超时时间，按毫秒精度设置
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn set_bsend_timeout(&self, timeout: Duration) -> Result<()> {
+        self.set_millis_param(InternalParam::BSendTimeout, timeout)
+    }
+
+    ///
+    /// 读取 BRecv 超时时间。
+    ///
+    /// **返回值:**
+    ///  - Ok: 超时时间
+    ///  - Err: 操作失败
+    ///
+    pub fn brecv_timeout(&self) -> Result<Duration> {
+        self.param(InternalParam::BRecvTimeout, InternalParamValue::as_millis)
+    }
+
+    ///
+    /// 设置 BRecv 超时时间，即接收一个数据包中各分段的最长等待时间。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - timeout: 超时时间，按毫秒精度设置
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn set_brecv_timeout(&self, timeout: Duration) -> Result<()> {
+        self.set_millis_param(InternalParam::BRecvTimeout, timeout)
+    }
+
     ///
     /// 启动伙伴将其绑定到指定的 IP 地址和 TCP 端口。
     ///
@@ -286,12 +385,62 @@ impl S7Partner {
                 rem_tsap,
             );
             if res == 0 {
+                self.remote_tsap.store(rem_tsap, Ordering::Relaxed);
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
         }
     }
 
+    ///
+    /// 以 Tsap 类型指定本地与远程 TSAP 启动伙伴，见 start_to。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - local_address: 本地服务器地址
+    ///  - remote_address: 远程服务器地址
+    ///  - local: 本地 TSAP
+    ///  - remote: 远程 TSAP
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn start_to_tsap(
+        &self,
+        local_address: &str,
+        remote_address: &str,
+        local: Tsap,
+        remote: Tsap,
+    ) -> Result<()> {
+        self.start_to(local_address, remote_address, local.value(), remote.value())
+    }
+
+    ///
+    /// 读取当前连接的 TSAP 与端口信息。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 连接信息
+    ///  - Err: 操作失败
+    ///
+    pub fn connection_info(&self) -> Result<ConnectionInfo> {
+        let read_u16 = |param: InternalParam| -> Result<u16> {
+            let mut value = InternalParamValue::U16(0);
+            self.get_param(param, &mut value)?;
+            match value {
+                InternalParamValue::U16(v) => Ok(v),
+                _ => bail!("{}", Self::error_text(-1)),
+            }
+        };
+        Ok(ConnectionInfo {
+            local_tsap: read_u16(InternalParam::SrcTSap)?,
+            remote_tsap: self.remote_tsap.load(Ordering::Relaxed),
+            local_port: read_u16(InternalParam::LocalPort)?,
+            remote_port: read_u16(InternalParam::RemotePort)?,
+        })
+    }
+
     ///
     /// 启动伙伴并使用之前 start_to() 中指定的参数。
     ///
@@ -305,7 +454,7 @@ impl S7Partner {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
         }
     }
 
@@ -322,7 +471,7 @@ impl S7Partner {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
         }
     }
 
@@ -351,7 +500,7 @@ impl S7Partner {
     where
         F: FnMut(*mut c_void, c_int) + 'static,
     {
-        if callback.is_some() {
+        if let Some(callback) = callback {
             unsafe {
                 let data = Box::into_raw(Box::new(callback));
                 let res = Par_SetSendCallback(
@@ -362,7 +511,7 @@ impl S7Partner {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::partner(res))
             }
         } else {
             unsafe {
@@ -371,7 +520,7 @@ impl S7Partner {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::partner(res))
             }
         }
     }
@@ -387,18 +536,21 @@ impl S7Partner {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
+    /// 回调参数依次为: 用户指针、操作结果、r_id、数据指针、数据长度。
+    /// 其中 r_id 为 32 位无符号路由参数(u32)，与发送方 b_send()/as_b_send() 传入的值相同。
+    ///
     /// # Examples
     /// ```ignore
-    /// partner.set_recv_callback(Some(|_ptr, op, r_id, p_data: *mut c_void, size: i32| {
+    /// partner.set_recv_callback(Some(|_ptr, op, r_id: u32, p_data: *mut c_void, size: i32| {
     ///     let buff = std::slice::from_raw_parts(p_data, size as usize);
     ///     println!("op: {}, r_id:{}, p_data:{:#x?}", op, r_id, buff);
     /// })).unwrap();
     /// ```
     pub fn set_recv_callback<F>(&self, callback: Option<F>) -> Result<()>
     where
-        F: FnMut(*mut c_void, c_int, longword, *mut c_void, c_int) + 'static,
+        F: FnMut(*mut c_void, c_int, u32, *mut c_void, c_int) + 'static,
     {
-        if callback.is_some() {
+        if let Some(callback) = callback {
             unsafe {
                 let data = Box::into_raw(Box::new(callback));
                 let res = Par_SetRecvCallback(
@@ -409,7 +561,7 @@ impl S7Partner {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::partner(res))
             }
         } else {
             unsafe {
@@ -418,7 +570,7 @@ impl S7Partner {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::partner(res))
             }
         }
     }
@@ -446,10 +598,45 @@ impl S7Partner {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
         }
     }
 
+    ///
+    /// 设置默认路由参数，供 b_send_default 和 recv_default 使用，初始值为 0。
+    ///
+    /// 默认值只影响 *_default 系列函数，显式传入 r_id 的函数(b_send、recv 等)不受影响。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - r_id: 默认路由参数
+    ///
+    pub fn set_default_r_id(&self, r_id: u32) {
+        self.default_r_id.store(r_id, Ordering::Relaxed);
+    }
+
+    ///
+    /// 获取当前的默认路由参数。
+    ///
+    pub fn default_r_id(&self) -> u32 {
+        self.default_r_id.load(Ordering::Relaxed)
+    }
+
+    ///
+    /// 使用默认路由参数同步发送一个数据包，见 b_send。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - buff: 用户缓冲区
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn b_send_default(&self, buff: &mut [u8]) -> Result<()> {
+        self.b_send(self.default_r_id(), buff)
+    }
+
     ///
     /// 向伙伴发送一个数据包，这个函数是异步的，也就是说它会立即返回，需要一个检查方法来知道传输何时完成。
     ///
@@ -473,7 +660,7 @@ impl S7Partner {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
         }
     }
 
@@ -530,7 +717,7 @@ impl S7Partner {
     ///
     /// **输入参数:**
     ///
-    ///  - r_id: 路由参数，远程伙伴 b_send 应提供相同的值
+    ///  - r_id: 输出参数，接收到的路由参数，即远程伙伴 b_send 提供的值
     ///  - buff: 用户缓冲区
     ///  - size: 接收数据长度
     ///  - timeout: 超时，单位 ms
@@ -539,7 +726,13 @@ impl S7Partner {
     ///  - Ok: 操作成功
     ///  - Err: 操作失败
     ///
-    pub fn b_recv(&self, r_id: u32, buff: &mut [u8], size: &mut i32, timeout: u32) -> Result<()> {
+    pub fn b_recv(
+        &self,
+        r_id: &mut u32,
+        buff: &mut [u8],
+        size: &mut i32,
+        timeout: u32,
+    ) -> Result<()> {
         unsafe {
             let res = Par_BRecv(
                 self.handle,
@@ -551,8 +744,98 @@ impl S7Partner {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
+        }
+    }
+
+    ///
+    /// 从伙伴那里接收一个数据包，区分超时和真正的错误，缓冲区由内部管理。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - r_id: 输出参数，接收到的路由参数，即远程伙伴 b_send 提供的值
+    ///  - timeout: 超时，按毫秒精度使用
+    ///
+    /// **返回值:**
+    ///  - Ok(Some): 接收到的数据
+    ///  - Ok(None): 超时内没有收到数据
+    ///  - Err: 操作失败，如连接断开
+    ///
+    pub fn recv(&self, r_id: &mut u32, timeout: Duration) -> Result<Option<Vec<u8>>> {
+        let timeout = timeout.as_millis().min(u32::MAX as u128) as u32;
+        let mut buff = vec![0u8; MAX_BSEND_SIZE];
+        let mut size = 0;
+        let res = unsafe {
+            Par_BRecv(
+                self.handle,
+                r_id as *mut u32,
+                buff.as_mut_ptr() as *mut c_void,
+                &mut size as *mut c_int,
+                timeout,
+            )
+        };
+        if res == 0 {
+            buff.truncate(size as usize);
+            return Ok(Some(buff));
+        }
+        if res as u32 & 0xFFF0_0000 == errParRecvTimeout {
+            return Ok(None);
         }
+        bail!(Snap7Error::partner(res))
+    }
+
+    ///
+    /// 接收一个数据包，并要求其路由参数与默认路由参数一致，见 recv。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - timeout: 超时，按毫秒精度使用
+    ///
+    /// **返回值:**
+    ///  - Ok(Some): 接收到的数据
+    ///  - Ok(None): 超时内没有收到数据
+    ///  - Err: 操作失败，或数据包的路由参数与默认值不同(该数据包已被取走)
+    ///
+    pub fn recv_default(&self, timeout: Duration) -> Result<Option<Vec<u8>>> {
+        let mut r_id = 0;
+        let data = self.recv(&mut r_id, timeout)?;
+        let expected = self.default_r_id();
+        if data.is_some() && r_id != expected {
+            bail!(
+                "received r_id {:#x} but the default route is {:#x}",
+                r_id,
+                expected
+            );
+        }
+        Ok(data)
+    }
+
+    ///
+    /// 从伙伴那里接收一个数据包，并按 S7Struct 布局解码为结构体。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - r_id: 输出参数，接收到的路由参数，即远程伙伴 b_send 提供的值
+    ///  - timeout: 超时，按毫秒精度使用
+    ///
+    /// **返回值:**
+    ///  - Ok(Some): 解码后的结构体
+    ///  - Ok(None): 超时内没有收到数据
+    ///  - Err: 操作失败，或数据包长度与 T::SIZE 不一致
+    ///
+    pub fn recv_as<T: S7Struct>(&self, r_id: &mut u32, timeout: Duration) -> Result<Option<T>> {
+        let data = match self.recv(r_id, timeout)? {
+            Some(data) => data,
+            None => return Ok(None),
+        };
+        if data.len() != T::SIZE {
+            bail!(
+                "received {} bytes but the struct layout needs {} bytes",
+                data.len(),
+                T::SIZE
+            );
+        }
+        T::from_bytes(&data).map(Some).map_err(Error::msg)
     }
 
     ///
@@ -608,7 +891,7 @@ impl S7Partner {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
         }
     }
 
@@ -644,7 +927,7 @@ impl S7Partner {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
         }
     }
 
@@ -665,7 +948,7 @@ impl S7Partner {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
         }
     }
 
@@ -693,7 +976,7 @@ impl S7Partner {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::partner(res))
         }
     }
 
@@ -731,7 +1014,7 @@ unsafe extern "C" fn call_recv_closure<F>(
     p_data: *mut c_void,
     size: c_int,
 ) where
-    F: FnMut(*mut c_void, c_int, longword, *mut c_void, c_int),
+    F: FnMut(*mut c_void, c_int, u32, *mut c_void, c_int),
 {
     let callback_ptr = usr_ptr as *mut F;
     let callback = &mut *callback_ptr;
@@ -741,9 +1024,44 @@ unsafe extern "C" fn call_recv_closure<F>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::{getters, setters};
+    use std::result::Result::Ok;
+    use std::sync::{RwLock, RwLockWriteGuard};
+
+    // 被动伙伴都监听 102 端口，并按对端地址(均为 127.0.0.1)匹配连接，
+    // 需要独占连接的测试取写锁，避免被其它测试的伙伴抢走连接。
+    static PARTNER_PORT: RwLock<()> = RwLock::new(());
+
+    fn exclusive_port() -> RwLockWriteGuard<'static, ()> {
+        PARTNER_PORT.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn wait_linked(partner: &S7Partner) {
+        let mut status = 0;
+        for _ in 0..50 {
+            partner.get_status(&mut status).unwrap();
+            if status == 3 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+    }
+
+    /// 等待工作线程复位上一次 b_send 的发送标志，否则紧接着的发送会报告 Partner Busy。
+    fn wait_send_idle(partner: &S7Partner) {
+        let mut op_result = 0;
+        for _ in 0..300 {
+            if partner.check_as_b_send_completion(&mut op_result) == 0 {
+                return;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("the previous b_send is still pending");
+    }
 
     #[test]
     fn test_partner() {
+        let _shared = PARTNER_PORT.read().unwrap_or_else(|e| e.into_inner());
         std::thread::sleep(std::time::Duration::from_secs(1));
 
         let partner = S7Partner::create(0);
@@ -765,8 +1083,230 @@ mod tests {
         partner.stop().unwrap();
     }
 
+    #[test]
+    fn test_b_recv_r_id() {
+        let _port = exclusive_port();
+        let passive = S7Partner::create(0);
+        passive
+            .start_to("127.0.0.1", "127.0.0.1", 0x1003, 0x1003)
+            .unwrap();
+
+        let active = S7Partner::create(1);
+        active
+            .start_to("127.0.0.1", "127.0.0.1", 0x1003, 0x1003)
+            .unwrap();
+
+        std::thread::scope(|s| {
+            let receiver = s.spawn(|| {
+                let mut r_id = 0;
+                let mut buff = [0u8; 16];
+                let mut size = 0;
+                passive
+                    .b_recv(&mut r_id, &mut buff, &mut size, 3000)
+                    .unwrap();
+                (r_id, buff[..size as usize].to_vec())
+            });
+
+            wait_linked(&active);
+            let mut buff = [0x0au8, 0x0b, 0x0c];
+            active.b_send(0x55, &mut buff).unwrap();
+
+            let (r_id, data) = receiver.join().unwrap();
+            assert_eq!(r_id, 0x55);
+            assert_eq!(data, vec![0x0a, 0x0b, 0x0c]);
+        });
+
+        active.stop().unwrap();
+        passive.stop().unwrap();
+    }
+
+    #[test]
+    fn test_recv_timeout() {
+        let _port = exclusive_port();
+        let passive = S7Partner::create(0);
+        passive
+            .start_to("127.0.0.1", "127.0.0.1", 0x1007, 0x1007)
+            .unwrap();
+        let active = S7Partner::create(1);
+        active
+            .start_to("127.0.0.1", "127.0.0.1", 0x1007, 0x1007)
+            .unwrap();
+        wait_linked(&active);
+
+        let mut r_id = 0;
+        let received = passive.recv(&mut r_id, Duration::from_millis(300)).unwrap();
+        assert!(received.is_none());
+
+        let mut buff = [0x01u8, 0x02];
+        active.b_send(0x66, &mut buff).unwrap();
+        let received = passive.recv(&mut r_id, Duration::from_secs(3)).unwrap();
+        assert_eq!(received, Some(vec![0x01, 0x02]));
+        assert_eq!(r_id, 0x66);
+
+        active.stop().unwrap();
+        passive.stop().unwrap();
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Setpoint {
+        speed: f32,
+        count: i16,
+    }
+
+    impl S7Struct for Setpoint {
+        const SIZE: usize = 6;
+
+        fn to_bytes(&self, buff: &mut [u8]) -> Result<(), String> {
+            setters::set_real(buff, 0, self.speed);
+            setters::set_int(buff, 4, self.count);
+            Ok(())
+        }
+
+        fn from_bytes(buff: &[u8]) -> Result<Self, String> {
+            Ok(Setpoint {
+                speed: getters::get_real(buff, 0),
+                count: getters::get_int(buff, 4),
+            })
+        }
+    }
+
+    #[test]
+    fn test_recv_as() {
+        let _port = exclusive_port();
+        let passive = S7Partner::create(0);
+        passive
+            .start_to("127.0.0.1", "127.0.0.1", 0x1008, 0x1008)
+            .unwrap();
+        let active = S7Partner::create(1);
+        active
+            .start_to("127.0.0.1", "127.0.0.1", 0x1008, 0x1008)
+            .unwrap();
+        wait_linked(&active);
+
+        let mut buff = [0u8; Setpoint::SIZE];
+        Setpoint {
+            speed: 2.5,
+            count: -4,
+        }
+        .to_bytes(&mut buff)
+        .unwrap();
+        active.b_send(7, &mut buff).unwrap();
+        let mut r_id = 0;
+        let received = passive
+            .recv_as::<Setpoint>(&mut r_id, Duration::from_secs(3))
+            .unwrap();
+        assert_eq!(
+            received,
+            Some(Setpoint {
+                speed: 2.5,
+                count: -4
+            })
+        );
+        assert_eq!(r_id, 7);
+
+        wait_send_idle(&active);
+        let mut buff = [0u8; 3];
+        active.b_send(8, &mut buff).unwrap();
+        assert!(passive
+            .recv_as::<Setpoint>(&mut r_id, Duration::from_secs(3))
+            .is_err());
+
+        active.stop().unwrap();
+        passive.stop().unwrap();
+    }
+
+    #[test]
+    fn test_default_r_id() {
+        let _port = exclusive_port();
+        let passive = S7Partner::create(0);
+        passive
+            .start_to("127.0.0.1", "127.0.0.1", 0x1009, 0x1009)
+            .unwrap();
+        let active = S7Partner::create(1);
+        active
+            .start_to("127.0.0.1", "127.0.0.1", 0x1009, 0x1009)
+            .unwrap();
+        wait_linked(&active);
+
+        assert_eq!(active.default_r_id(), 0);
+        active.set_default_r_id(0x42);
+        passive.set_default_r_id(0x42);
+
+        let mut buff = [0x0du8, 0x0e];
+        active.b_send_default(&mut buff).unwrap();
+        let received = passive.recv_default(Duration::from_secs(3)).unwrap();
+        assert_eq!(received, Some(vec![0x0d, 0x0e]));
+
+        wait_send_idle(&active);
+        let mut buff = [0x0fu8];
+        active.b_send(0x43, &mut buff).unwrap();
+        assert!(passive.recv_default(Duration::from_secs(3)).is_err());
+
+        active.stop().unwrap();
+        passive.stop().unwrap();
+    }
+
+    #[test]
+    fn test_connection_info() {
+        let _port = exclusive_port();
+        let partner = S7Partner::create(1);
+        partner
+            .start_to_tsap(
+                "127.0.0.1",
+                "127.0.0.1",
+                Tsap::new(0x10, 0, 4),
+                Tsap::new(0x10, 0, 5),
+            )
+            .unwrap();
+
+        let info = partner.connection_info().unwrap();
+        assert_eq!(info.local_tsap, 0x1004);
+        assert_eq!(info.remote_tsap, 0x1005);
+        assert_eq!(info.remote_port, 102);
+
+        partner.stop().unwrap();
+    }
+
+    #[test]
+    fn test_recv_callback_r_id() {
+        let _port = exclusive_port();
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let passive = S7Partner::create(0);
+        passive
+            .set_recv_callback(Some(
+                move |_, op, r_id: u32, p_data: *mut c_void, size: i32| {
+                    let buff =
+                        unsafe { std::slice::from_raw_parts(p_data as *const u8, size as usize) };
+                    let _ = tx.send((op, r_id, buff.to_vec()));
+                },
+            ))
+            .unwrap();
+        passive
+            .start_to("127.0.0.1", "127.0.0.1", 0x1006, 0x1006)
+            .unwrap();
+
+        let active = S7Partner::create(1);
+        active
+            .start_to("127.0.0.1", "127.0.0.1", 0x1006, 0x1006)
+            .unwrap();
+        wait_linked(&active);
+
+        let mut buff = [0x11u8, 0x22];
+        active.b_send(0xDEAD_BEEF, &mut buff).unwrap();
+
+        let (op, r_id, data) = rx.recv_timeout(std::time::Duration::from_secs(3)).unwrap();
+        assert_eq!(op, 0);
+        assert_eq!(r_id, 0xDEAD_BEEF);
+        assert_eq!(data, vec![0x11, 0x22]);
+
+        active.stop().unwrap();
+        passive.stop().unwrap();
+    }
+
     #[test]
     fn test_active_partner() {
+        let _shared = PARTNER_PORT.read().unwrap_or_else(|e| e.into_inner());
         // 等待伙伴启动
         std::thread::sleep(std::time::Duration::from_secs(2));
 
@@ -843,4 +1383,36 @@ mod tests {
 
         partner.stop().unwrap();
     }
+
+    #[test]
+    fn test_params() {
+        let partner = S7Partner::create(1);
+        partner
+            .set_bsend_timeout(Duration::from_millis(1500))
+            .unwrap();
+        partner
+            .set_param(InternalParam::RecoveryTime, InternalParamValue::U32(750))
+            .unwrap();
+
+        let params = partner.params().unwrap();
+        assert_eq!(params.bsend_timeout, Duration::from_millis(1500));
+        assert_eq!(params.recovery_time, Duration::from_millis(750));
+    }
+
+    #[test]
+    fn test_bsend_brecv_timeouts() {
+        let partner = S7Partner::create(1);
+        partner
+            .set_bsend_timeout(Duration::from_millis(1500))
+            .unwrap();
+        partner.set_brecv_timeout(Duration::from_secs(4)).unwrap();
+        assert_eq!(
+            partner.bsend_timeout().unwrap(),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(partner.brecv_timeout().unwrap(), Duration::from_secs(4));
+        assert!(partner
+            .set_bsend_timeout(Duration::from_secs(u64::MAX))
+            .is_err());
+    }
 }
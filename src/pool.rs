@@ -0,0 +1,181 @@
+//
+// pool.rs
+// Copyright (C) 2021 gmg137 <gmg137 AT live.com>
+// snap7-rs is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//          http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+// EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+// MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+//
+use crate::client::S7Client;
+use anyhow::*;
+use std::{
+    ops::Deref,
+    sync::{Condvar, Mutex},
+};
+
+/// S7 客户端连接池
+///
+/// 一个 S7Client 同一时间只能执行一个作业，连接池维护 N 个连接到同一 PLC 的客户端，
+/// 多个线程可以通过它并行读写。
+///
+/// # Examples
+/// ```ignore
+/// use rust_snap7::ClientPool;
+///
+/// let pool = ClientPool::create(4, |client| client.connect_to("192.168.1.123", 0, 1)).unwrap();
+/// std::thread::scope(|s| {
+///     for _ in 0..8 {
+///         s.spawn(|| {
+///             let client = pool.get().unwrap();
+///             let mut buff = [0u8; 2];
+///             client.db_read(1, 20, 2, &mut buff).unwrap();
+///         });
+///     }
+/// });
+/// ```
+pub struct ClientPool {
+    clients: Mutex<Vec<S7Client>>,
+    available: Condvar,
+    size: usize,
+}
+
+impl ClientPool {
+    ///
+    /// 创建连接池。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - size: 连接数量
+    ///  - init: 初始化函数，用于设置参数并连接每一个客户端(如 connect_to())
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作成功
+    ///  - Err: 任意一个客户端初始化失败
+    ///
+    pub fn create<F>(size: usize, init: F) -> Result<ClientPool>
+    where
+        F: Fn(&S7Client) -> Result<()>,
+    {
+        if size == 0 {
+            bail!("ClientPool size must be greater than 0");
+        }
+        let mut clients = Vec::with_capacity(size);
+        for _ in 0..size {
+            let client = S7Client::create();
+            init(&client)?;
+            clients.push(client);
+        }
+        Ok(ClientPool {
+            clients: Mutex::new(clients),
+            available: Condvar::new(),
+            size,
+        })
+    }
+
+    /// 连接池中客户端的总数。
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    ///
+    /// 取出一个空闲的客户端，如果没有空闲客户端则阻塞等待。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 客户端守卫，drop 时自动归还
+    ///  - Err: 客户端已断开且重新连接失败
+    ///
+    /// `注：取出时会检查连接状态，已断开的客户端将使用先前的参数调用 connect() 重新连接。`
+    ///
+    pub fn get(&self) -> Result<PooledClient<'_>> {
+        let mut clients = self.clients.lock().unwrap();
+        let client = loop {
+            match clients.pop() {
+                Some(client) => break client,
+                None => clients = self.available.wait(clients).unwrap(),
+            }
+        };
+        drop(clients);
+
+        let guard = PooledClient {
+            pool: self,
+            client: Some(client),
+        };
+        guard.ensure_connected()?;
+        Ok(guard)
+    }
+
+    fn put_back(&self, client: S7Client) {
+        self.clients.lock().unwrap().push(client);
+        self.available.notify_one();
+    }
+}
+
+/// 从连接池中取出的客户端，drop 时归还连接池。
+pub struct PooledClient<'a> {
+    pool: &'a ClientPool,
+    client: Option<S7Client>,
+}
+
+impl Deref for PooledClient<'_> {
+    type Target = S7Client;
+
+    fn deref(&self) -> &S7Client {
+        self.client.as_ref().unwrap()
+    }
+}
+
+impl Drop for PooledClient<'_> {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            self.pool.put_back(client);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{model::*, test_support::*};
+
+    #[test]
+    fn test_client_pool() {
+        let mut db_buff = [0u8; 64];
+        db_buff[0..2].copy_from_slice(&1234u16.to_be_bytes());
+        let (server, port) = start_server(&mut [(AreaCode::S7AreaDB, 1, &mut db_buff)]);
+
+        let pool = ClientPool::create(3, |client| {
+            client.set_param(InternalParam::RemotePort, InternalParamValue::U16(port))?;
+            client.connect_to("127.0.0.1", 0, 1)
+        })
+        .unwrap();
+        assert_eq!(pool.size(), 3);
+
+        std::thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..5 {
+                        let client = pool.get().unwrap();
+                        let mut buff = [0u8; 2];
+                        client.db_read(1, 0, 2, &mut buff).unwrap();
+                        assert_eq!(u16::from_be_bytes(buff), 1234);
+                    }
+                });
+            }
+        });
+
+        // 断开的连接在取出时重新连接
+        pool.get().unwrap().disconnect().unwrap();
+        let client = pool.get().unwrap();
+        let mut buff = [0u8; 2];
+        assert!(client.db_read(1, 0, 2, &mut buff).is_ok());
+        drop(client);
+
+        server.stop().unwrap();
+    }
+}
@@ -0,0 +1,203 @@
+//
+// reliable.rs
+// Copyright (C) 2021 gmg137 <gmg137 AT live.com>
+// snap7-rs is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//          http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+// EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+// MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+//
+use crate::{client::S7Client, error::*, model::*};
+use anyhow::*;
+use std::ops::Deref;
+
+/// 自动重连的 S7 客户端
+///
+/// 每次读写前确认连接，遇到连接层错误(ErrorClass::Tcp/Iso)时按 RetryPolicy 等待、
+/// 使用原有连接参数重新连接并重试；其它错误直接返回。
+///
+/// # Examples
+/// ```ignore
+/// use rust_snap7::{ReliableClient, RetryPolicy, S7Client};
+///
+/// let client = S7Client::create();
+/// client.connect_to("192.168.1.123", 0, 1)?;
+/// let client = ReliableClient::new(client, RetryPolicy::default());
+/// let mut buff = [0u8; 4];
+/// client.db_read(1, 0, 4, &mut buff)?;
+/// ```
+pub struct ReliableClient {
+    client: S7Client,
+    policy: RetryPolicy,
+}
+
+impl ReliableClient {
+    ///
+    /// 包装一个已设置连接参数(connect_to() 或 set_connection_params())的客户端。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - client: 客户端
+    ///  - policy: 重试策略
+    ///
+    pub fn new(client: S7Client, policy: RetryPolicy) -> Self {
+        ReliableClient { client, policy }
+    }
+
+    /// 当前的重试策略
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    /// 取回内部的客户端
+    pub fn into_inner(self) -> S7Client {
+        self.client
+    }
+
+    ///
+    /// 在连接层错误时自动重连并重试执行一个操作。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - op: 使用客户端执行的操作，重试时会被再次调用
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 操作结果
+    ///  - Err: 非连接层错误，或重试次数耗尽后的最后一个错误
+    ///
+    pub fn execute<T, F>(&self, mut op: F) -> Result<T>
+    where
+        F: FnMut(&S7Client) -> Result<T>,
+    {
+        let mut attempt = 0;
+        loop {
+            let result = self
+                .client
+                .ensure_connected()
+                .and_then(|_| op(&self.client));
+            match result {
+                Err(e) if attempt < self.policy.max_retries && Self::is_link_error(&e) => {
+                    attempt += 1;
+                    std::thread::sleep(self.policy.backoff);
+                    // 重连失败时由下一次尝试的 ensure_connected() 继续处理
+                    let _ = self.client.reconnect();
+                }
+                result => return result,
+            }
+        }
+    }
+
+    fn is_link_error(error: &Error) -> bool {
+        error
+            .downcast_ref::<Snap7Error>()
+            .is_some_and(|e| matches!(e.class(), ErrorClass::Tcp | ErrorClass::Iso))
+    }
+
+    /// 带重试的 read_area()
+    pub fn read_area(
+        &self,
+        area: AreaTable,
+        db_number: i32,
+        start: i32,
+        size: i32,
+        word_len: WordLenTable,
+        buff: &mut [u8],
+    ) -> Result<()> {
+        self.execute(|client| client.read_area(area, db_number, start, size, word_len, buff))
+    }
+
+    /// 带重试的 write_area()
+    pub fn write_area(
+        &self,
+        area: AreaTable,
+        db_number: i32,
+        start: i32,
+        size: i32,
+        word_len: WordLenTable,
+        buff: &mut [u8],
+    ) -> Result<()> {
+        self.execute(|client| client.write_area(area, db_number, start, size, word_len, buff))
+    }
+
+    /// 带重试的 db_read()
+    pub fn db_read(&self, db_number: i32, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        self.execute(|client| client.db_read(db_number, start, size, buff))
+    }
+
+    /// 带重试的 db_write()
+    pub fn db_write(&self, db_number: i32, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        self.execute(|client| client.db_write(db_number, start, size, buff))
+    }
+
+    /// 带重试的 mb_read()
+    pub fn mb_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        self.execute(|client| client.mb_read(start, size, buff))
+    }
+
+    /// 带重试的 mb_write()
+    pub fn mb_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        self.execute(|client| client.mb_write(start, size, buff))
+    }
+
+    /// 带重试的 eb_read()
+    pub fn eb_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        self.execute(|client| client.eb_read(start, size, buff))
+    }
+
+    /// 带重试的 ab_read()
+    pub fn ab_read(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        self.execute(|client| client.ab_read(start, size, buff))
+    }
+
+    /// 带重试的 ab_write()
+    pub fn ab_write(&self, start: i32, size: i32, buff: &mut [u8]) -> Result<()> {
+        self.execute(|client| client.ab_write(start, size, buff))
+    }
+}
+
+impl Deref for ReliableClient {
+    type Target = S7Client;
+
+    fn deref(&self) -> &S7Client {
+        &self.client
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_reconnect_after_server_restart() {
+        let mut db_buff = [1u8, 2, 3, 4];
+        let (server, client) = connect_local(&mut db_buff);
+        let client = ReliableClient::new(
+            client,
+            RetryPolicy {
+                max_retries: 5,
+                backoff: Duration::from_millis(100),
+            },
+        );
+        let mut buff = [0u8; 4];
+        client.db_read(1, 0, 4, &mut buff).unwrap();
+
+        // 服务端重启会断开现有连接，下一次读取需要重新连接
+        server.stop().unwrap();
+        server.start().unwrap();
+        buff = [0; 4];
+        client.db_read(1, 0, 4, &mut buff).unwrap();
+        assert_eq!(buff, [1, 2, 3, 4]);
+
+        // 非连接层错误不重试
+        assert!(client.db_read(9, 0, 4, &mut buff).is_err());
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+    }
+}
@@ -10,18 +10,25 @@
 // MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
 // See the Mulan PSL v2 for more details.
 //
-use crate::{ffi::*, model::*};
+use crate::{error::*, ffi::*, model::*};
 use anyhow::*;
 use std::{
+    collections::HashMap,
     ffi::{CStr, CString},
+    marker::PhantomData,
     os::raw::*,
+    sync::{
+        mpsc::{Sender, SyncSender},
+        Arc, Mutex,
+    },
+    time::Duration,
 };
 
 /// S7 服务端
 ///
 /// # Examples
 /// ```
-/// use snap7_rs::{AreaCode, InternalParam, InternalParamValue, S7Server, MaskKind};
+/// use rust_snap7::{AreaCode, InternalParam, InternalParamValue, S7Server, MaskKind};
 /// use std::ffi::*;
 /// use std::os::raw::*;
 ///
@@ -65,6 +72,47 @@ use std::{
 /// ```
 pub struct S7Server {
     handle: usize,
+    /// 由服务端分配并持有的共享内存区，在 Srv_Destroy 之后释放
+    owned_areas: Mutex<HashMap<(AreaCode, u16), Arc<OwnedArea>>>,
+    /// forward_events_to() 或 set_events_handler_threaded() 的事件发送端，清空后接收端随之结束
+    event_queue: Mutex<Option<EventQueue>>,
+}
+
+type EventQueue = Arc<Mutex<Option<EventSender>>>;
+
+/// 事件转发所用的发送端
+#[derive(Clone)]
+enum EventSender {
+    /// forward_events_to() 的通道
+    Channel(Sender<ServerEvent>),
+    /// set_events_handler_threaded() 的有界队列
+    Queue(SyncSender<ServerEvent>, QueueFullPolicy),
+}
+
+impl EventSender {
+    fn send(&self, event: ServerEvent) {
+        match self {
+            EventSender::Channel(tx) => {
+                let _ = tx.send(event);
+            }
+            EventSender::Queue(tx, QueueFullPolicy::Drop) => {
+                let _ = tx.try_send(event);
+            }
+            EventSender::Queue(tx, QueueFullPolicy::Block) => {
+                let _ = tx.send(event);
+            }
+        }
+    }
+}
+
+impl ParamAccess for S7Server {
+    fn get_param(&self, param: InternalParam, value: &mut InternalParamValue) -> Result<()> {
+        S7Server::get_param(self, param, value)
+    }
+
+    fn set_param(&self, param: InternalParam, value: InternalParamValue) -> Result<()> {
+        S7Server::set_param(self, param, value)
+    }
 }
 
 impl Drop for S7Server {
@@ -72,6 +120,7 @@ impl Drop for S7Server {
         unsafe {
             Srv_Destroy(&mut self.handle as *mut S7Object);
         }
+        self.close_event_queue();
     }
 }
 
@@ -86,6 +135,8 @@ impl S7Server {
     pub fn create() -> Self {
         S7Server {
             handle: unsafe { Srv_Create() },
+            owned_areas: Mutex::new(HashMap::new()),
+            event_queue: Mutex::new(None),
         }
     }
 
@@ -115,7 +166,7 @@ impl S7Server {
                     *value = InternalParamValue::U32(u32::from_le_bytes(buff));
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::server(res))
             },
             InternalParam::LocalPort
             | InternalParam::RemotePort
@@ -132,7 +183,7 @@ impl S7Server {
                     *value = InternalParamValue::U16(u16::from_le_bytes(buff));
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::server(res))
             },
             _ => unsafe {
                 let mut buff = [0u8; 4];
@@ -145,7 +196,7 @@ impl S7Server {
                     *value = InternalParamValue::I32(i32::from_le_bytes(buff));
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::server(res))
             },
         }
     }
@@ -176,7 +227,7 @@ impl S7Server {
                     if res == 0 {
                         return Ok(());
                     }
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::server(res))
                 } else {
                     bail!("{}", Self::error_text(-1))
                 }
@@ -196,7 +247,7 @@ impl S7Server {
                     if res == 0 {
                         return Ok(());
                     }
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::server(res))
                 } else {
                     bail!("{}", Self::error_text(-1))
                 }
@@ -212,7 +263,7 @@ impl S7Server {
                     if res == 0 {
                         return Ok(());
                     }
-                    bail!("{}", Self::error_text(res))
+                    bail!(Snap7Error::server(res))
                 } else {
                     bail!("{}", Self::error_text(-1))
                 }
@@ -220,6 +271,97 @@ impl S7Server {
         }
     }
 
+    ///
+    /// 读取服务端支持的所有内部参数，便于诊断输出当前配置。
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: ServerParams 结构体
+    ///  - Err: 读取某个参数失败
+    ///
+    pub fn params(&self) -> Result<ServerParams> {
+        use InternalParamValue as V;
+        Ok(ServerParams {
+            local_port: self.param(InternalParam::LocalPort, V::as_u16)?,
+            work_interval: self.param(InternalParam::WorkInterval, V::as_millis)?,
+            max_clients: self.param(InternalParam::MaxClients, V::as_i32)?,
+            pdu_request: self.param(InternalParam::PDURequest, V::as_i32)?,
+        })
+    }
+
+    ///
+    /// 读取服务端允许的最大客户端连接数。
+    ///
+    /// **返回值:**
+    ///  - Ok: 最大客户端连接数
+    ///  - Err: 操作失败
+    ///
+    pub fn max_clients(&self) -> Result<i32> {
+        let mut value = InternalParamValue::I32(0);
+        self.get_param(InternalParam::MaxClients, &mut value)?;
+        match value {
+            InternalParamValue::I32(n) => Ok(n),
+            _ => bail!("{}", Self::error_text(-1)),
+        }
+    }
+
+    ///
+    /// 设置服务端允许的最大客户端连接数。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - n: 最大客户端连接数，必须大于 0
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    /// `注：只有在服务端停止且没有客户端连接时才能修改。`
+    ///
+    pub fn set_max_clients(&self, n: i32) -> Result<()> {
+        if n <= 0 {
+            bail!("max clients must be greater than 0, got {}", n);
+        }
+        self.set_param(InternalParam::MaxClients, InternalParamValue::I32(n))
+    }
+
+    ///
+    /// 读取服务端的 Socket 作业间隔。
+    ///
+    /// **返回值:**
+    ///  - Ok: 作业间隔
+    ///  - Err: 操作失败
+    ///
+    pub fn work_interval(&self) -> Result<Duration> {
+        let mut value = InternalParamValue::I32(0);
+        self.get_param(InternalParam::WorkInterval, &mut value)?;
+        match value {
+            InternalParamValue::I32(ms) if ms >= 0 => Ok(Duration::from_millis(ms as u64)),
+            _ => bail!("{}", Self::error_text(-1)),
+        }
+    }
+
+    ///
+    /// 设置服务端的 Socket 作业间隔。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - interval: 作业间隔，按毫秒精度设置
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    pub fn set_work_interval(&self, interval: Duration) -> Result<()> {
+        if interval.as_millis() > i32::MAX as u128 {
+            bail!("work interval too large: {:?}", interval);
+        }
+        self.set_param(
+            InternalParam::WorkInterval,
+            InternalParamValue::I32(interval.as_millis() as i32),
+        )
+    }
+
     ///
     /// 启动服务端并将其绑定到指定的 IP 地址和 TCP 端口。
     ///
@@ -238,7 +380,7 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
@@ -257,7 +399,7 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
@@ -274,7 +416,7 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
@@ -284,14 +426,21 @@ impl S7Server {
     /// **输入参数:**
     ///
     ///  - area_code: 区块类型
-    ///  - index: 要分享的数据块(DB)编号。如果 area_code != S7AreaDB 则被忽略，值为 0。
+    ///  - index: 要分享的数据块(DB)编号，即客户端 db_read() 等函数中使用的 DB 号。如果 area_code != S7AreaDB 则必须为 0。
     ///  - buff: 要分享的内存缓冲区
     ///
     /// **返回值:**
     ///  - Ok: 操作成功
-    ///  - Err: 操作失败
+    ///  - Err: 操作失败，或非 DB 区域传入了非 0 的 index
     ///
     pub fn register_area(&self, area_code: AreaCode, index: u16, buff: &mut [u8]) -> Result<()> {
+        if area_code != AreaCode::S7AreaDB && index != 0 {
+            bail!(
+                "index {} is only meaningful for S7AreaDB, use 0 for {:?}",
+                index,
+                area_code
+            );
+        }
         unsafe {
             let res = Srv_RegisterArea(
                 self.handle,
@@ -303,10 +452,113 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
+    ///
+    /// 分配一个由服务端持有的内存区域并以 initial 的内容初始化后共享。
+    ///
+    /// 与 register_area() 不同，缓冲区的生命周期由服务端管理，因此该区域可以被
+    /// snapshot_areas() 和 restore_areas() 安全地访问。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - area_code: 区块类型
+    ///  - index: 数据块(DB)编号。如果 area_code != S7AreaDB 则必须为 0。
+    ///  - initial: 区域的初始内容，其长度即区域大小
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败，或非 DB 区域传入了非 0 的 index
+    ///
+    pub fn register_owned_area(
+        &self,
+        area_code: AreaCode,
+        index: u16,
+        initial: &[u8],
+    ) -> Result<()> {
+        if area_code != AreaCode::S7AreaDB && index != 0 {
+            bail!(
+                "index {} is only meaningful for S7AreaDB, use 0 for {:?}",
+                index,
+                area_code
+            );
+        }
+        let area = Arc::new(OwnedArea::new(initial.len()));
+        unsafe { area.as_mut_slice() }.copy_from_slice(initial);
+        let res = unsafe {
+            Srv_RegisterArea(
+                self.handle,
+                area_code as c_int,
+                index,
+                area.ptr as *mut c_void,
+                area.len as c_int,
+            )
+        };
+        if res != 0 {
+            bail!(Snap7Error::server(res))
+        }
+        self.owned_areas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((area_code, index), area);
+        Ok(())
+    }
+
+    ///
+    /// 分配一个与结构体布局大小一致的 DB 并注册，返回可读写该结构体的类型化句柄。
+    ///
+    /// 缓冲区由服务端持有，客户端读写的正是该缓冲区，句柄的 get()/set() 会在区域锁内完成转换。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - db_index: 数据块(DB)编号
+    ///  - initial: 初始值
+    ///
+    /// **返回值:**
+    ///  - Ok: 类型化句柄
+    ///  - Err: 初始值编码失败或注册失败
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let motor = server.register_struct(1, Motor { speed: 0.0, running: false })?;
+    /// motor.set(&Motor { speed: 12.5, running: true })?;
+    /// let current = motor.get()?; // 包含客户端写入的修改
+    /// ```
+    pub fn register_struct<T: S7Struct>(
+        &self,
+        db_index: u16,
+        initial: T,
+    ) -> Result<StructHandle<'_, T>> {
+        let area = Arc::new(OwnedArea::new(T::SIZE));
+        initial
+            .to_bytes(unsafe { area.as_mut_slice() })
+            .map_err(Error::msg)?;
+        let res = unsafe {
+            Srv_RegisterArea(
+                self.handle,
+                AreaCode::S7AreaDB as c_int,
+                db_index,
+                area.ptr as *mut c_void,
+                T::SIZE as c_int,
+            )
+        };
+        if res != 0 {
+            bail!(Snap7Error::server(res))
+        }
+        self.owned_areas
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert((AreaCode::S7AreaDB, db_index), area.clone());
+        Ok(StructHandle {
+            server: self,
+            index: db_index,
+            area,
+            marker: PhantomData,
+        })
+    }
+
     ///
     /// 解除先前 register_area() 共享的内存区域，该内存块将不再被客户端看到。
     ///
@@ -323,12 +575,83 @@ impl S7Server {
         unsafe {
             let res = Srv_UnregisterArea(self.handle, area_code as c_int, index);
             if res == 0 {
+                self.owned_areas
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .remove(&(area_code, index));
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
+    ///
+    /// 复制所有由服务端持有的区域的当前内容，用于保存模拟器状态。
+    ///
+    /// 只包含 register_owned_area() 和 register_struct() 注册的区域；register_area() 共享的是调用者的缓冲区，
+    /// 服务端无法保证其仍然有效，因此不包含在快照中。
+    /// 每个区域在各自的区域锁内复制，因此单个区域的内容是一致的，但不同区域之间不是同一时刻的快照。
+    ///
+    /// **返回值:**
+    ///
+    ///  - 以 (区块类型, 编号) 为键的区域内容副本
+    ///
+    pub fn snapshot_areas(&self) -> HashMap<(AreaCode, u16), Vec<u8>> {
+        let areas = self.owned_areas.lock().unwrap_or_else(|e| e.into_inner());
+        areas
+            .iter()
+            .filter_map(|(&(area_code, index), area)| {
+                let _lock = self.lock(area_code, index).ok()?;
+                Some(((area_code, index), unsafe { area.as_mut_slice() }.to_vec()))
+            })
+            .collect()
+    }
+
+    ///
+    /// 将 snapshot_areas() 得到的快照写回对应的由服务端持有的区域，每个区域在各自的区域锁内写入。
+    ///
+    /// register_area() 共享的调用者缓冲区不会被写入，严格模式下视为未注册。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - snapshot: 以 (区块类型, 编号) 为键的区域内容
+    ///  - strict: true 时快照中存在未注册或大小不一致的区域则返回错误且不写入任何区域，
+    ///    false 时跳过这些区域
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 严格模式下区域不匹配，或加锁失败
+    ///
+    pub fn restore_areas(
+        &self,
+        snapshot: &HashMap<(AreaCode, u16), Vec<u8>>,
+        strict: bool,
+    ) -> Result<()> {
+        let areas = self.owned_areas.lock().unwrap_or_else(|e| e.into_inner());
+        let mut matched = Vec::with_capacity(snapshot.len());
+        for (&(area_code, index), data) in snapshot {
+            match areas.get(&(area_code, index)) {
+                Some(area) if area.len == data.len() => {
+                    matched.push((area_code, index, area, data))
+                }
+                Some(area) if strict => bail!(
+                    "{:?} {} is {} bytes but the snapshot has {} bytes",
+                    area_code,
+                    index,
+                    area.len,
+                    data.len()
+                ),
+                None if strict => bail!("{:?} {} is not registered", area_code, index),
+                _ => {}
+            }
+        }
+        for (area_code, index, area, data) in matched {
+            let _lock = self.lock(area_code, index)?;
+            unsafe { area.as_mut_slice() }.copy_from_slice(data);
+        }
+        Ok(())
+    }
+
     ///
     /// 锁定一个共享内存区域。
     ///
@@ -347,7 +670,7 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
@@ -369,10 +692,38 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
+    ///
+    /// 锁定一个共享内存区域，并返回在 Drop 时自动解锁的守卫。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - area_code: 区块类型
+    ///  - index: 要锁定的数据块(DB)编号。如果 area_code != S7AreaDB 则被忽略，值为 0。
+    ///
+    /// **返回值:**
+    ///  - Ok: 区域锁守卫
+    ///  - Err: 操作失败
+    ///
+    /// # Examples
+    /// ```ignore
+    /// {
+    ///     let _guard = server.lock(AreaCode::S7AreaDB, 1)?;
+    ///     db_buff[0] = 0x01;
+    /// } // 离开作用域后自动解锁
+    /// ```
+    pub fn lock(&self, area_code: AreaCode, index: u16) -> Result<AreaLock<'_>> {
+        self.lock_area(area_code, index)?;
+        Ok(AreaLock {
+            server: self,
+            area_code,
+            index,
+        })
+    }
+
     ///
     /// 设置服务器对象在创建事件时要调用的用户回调。
     ///
@@ -404,7 +755,15 @@ impl S7Server {
     where
         F: FnMut(*mut c_void, PSrvEvent, c_int) + 'static,
     {
-        if callback.is_some() {
+        self.close_event_queue();
+        self.install_events_callback(callback)
+    }
+
+    fn install_events_callback<F>(&self, callback: Option<F>) -> Result<()>
+    where
+        F: FnMut(*mut c_void, PSrvEvent, c_int) + 'static,
+    {
+        if let Some(callback) = callback {
             unsafe {
                 let data = Box::into_raw(Box::new(callback));
                 let res = Srv_SetEventsCallback(
@@ -415,7 +774,7 @@ impl S7Server {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::server(res))
             }
         } else {
             unsafe {
@@ -424,8 +783,110 @@ impl S7Server {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::server(res))
+            }
+        }
+    }
+
+    ///
+    /// 将服务端事件解码后转发到指定的通道。
+    ///
+    /// 该函数会替换之前设置的事件回调。发送端由服务端持有，再次设置事件回调
+    /// 或服务端释放时随之释放，此时接收端的迭代结束；接收端被释放后，后续事件将被静默丢弃。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - tx: 事件通道的发送端
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let (tx, rx) = std::sync::mpsc::channel();
+    /// server.forward_events_to(tx).unwrap();
+    /// for event in rx {
+    ///     println!("{:?}", event);
+    /// }
+    /// ```
+    pub fn forward_events_to(&self, tx: Sender<ServerEvent>) -> Result<()> {
+        self.set_event_sender(EventSender::Channel(tx))
+    }
+
+    ///
+    /// 在独立的 Rust 线程中处理服务端事件。
+    ///
+    /// snap7 线程只把解码后的事件放入容量为 queue_size 的队列后立即返回，
+    /// 耗时的处理不会拖慢服务端。该函数会替换之前设置的事件回调；
+    /// 再次调用、传入 None 或服务端释放时，旧的处理线程在处理完队列中剩余的事件后退出。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - handler: 事件处理函数，None 表示取消
+    ///  - queue_size: 队列容量
+    ///  - policy: 队列已满时丢弃新事件还是阻塞 snap7 线程
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    /// # Examples
+    /// ```ignore
+    /// server
+    ///     .set_events_handler_threaded(
+    ///         Some(|event: ServerEvent| println!("{:?}", event)),
+    ///         256,
+    ///         QueueFullPolicy::Drop,
+    ///     )
+    ///     .unwrap();
+    /// ```
+    pub fn set_events_handler_threaded<F>(
+        &self,
+        handler: Option<F>,
+        queue_size: usize,
+        policy: QueueFullPolicy,
+    ) -> Result<()>
+    where
+        F: FnMut(ServerEvent) + Send + 'static,
+    {
+        let mut handler = match handler {
+            Some(handler) => handler,
+            None => return self.set_events_callback(None::<fn(*mut c_void, PSrvEvent, c_int)>),
+        };
+        let (tx, rx) = std::sync::mpsc::sync_channel(queue_size);
+        std::thread::Builder::new()
+            .name("s7server-events".to_string())
+            .spawn(move || {
+                for event in rx {
+                    handler(event);
+                }
+            })?;
+        self.set_event_sender(EventSender::Queue(tx, policy))
+    }
+
+    fn set_event_sender(&self, sender: EventSender) -> Result<()> {
+        self.close_event_queue();
+        let queue: EventQueue = Arc::new(Mutex::new(Some(sender)));
+        *self.event_queue.lock().unwrap_or_else(|e| e.into_inner()) = Some(queue.clone());
+        self.install_events_callback(Some(move |_, p_event: PSrvEvent, _| {
+            let event = ServerEvent::from(unsafe { *p_event });
+            // 先取出发送端再发送，阻塞等待队列空间时不持有锁，close_event_queue() 才不会死锁
+            let sender = queue.lock().unwrap_or_else(|e| e.into_inner()).clone();
+            if let Some(sender) = sender {
+                sender.send(event);
             }
+        }))
+    }
+
+    fn close_event_queue(&self) {
+        let queue = self
+            .event_queue
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take();
+        if let Some(queue) = queue {
+            queue.lock().unwrap_or_else(|e| e.into_inner()).take();
         }
     }
 
@@ -454,14 +915,10 @@ impl S7Server {
     ///                 println!("写请求!");
     ///             }
     ///             let p7 = *ps7tag;
-    ///             match p7.Area {
-    ///                 0x81 => println!("Area: PE"),
-    ///                 0x82 => println!("Area: PA"),
-    ///                 0x83 => println!("Area: MK"),
-    ///                 0x1c => println!("Area: CT"),
-    ///                 0x1d => println!("Area: TM"),
-    ///                 0x84 => println!("Area: DB{}", p7.DBNumber as i32),
-    ///                 _ => println!("未定义的 Area"),
+    ///             match AreaTable::from_raw(p7.Area) {
+    ///                 Some(AreaTable::S7AreaDB) => println!("Area: DB{}", p7.DBNumber as i32),
+    ///                 Some(area) => println!("Area: {:?}", area),
+    ///                 None => println!("未定义的 Area"),
     ///             }
     ///             println!("Strat: {}", p7.Start as i32);
     ///             println!("Size: {}", p7.Size as i32);
@@ -479,7 +936,7 @@ impl S7Server {
     where
         F: FnMut(*mut c_void, c_int, c_int, PS7Tag, *mut c_void),
     {
-        if callback.is_some() {
+        if let Some(callback) = callback {
             unsafe {
                 let data = Box::into_raw(Box::new(callback));
                 let res = Srv_SetRWAreaCallback(
@@ -490,7 +947,7 @@ impl S7Server {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::server(res))
             }
         } else {
             unsafe {
@@ -499,11 +956,58 @@ impl S7Server {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::server(res))
             }
         }
     }
 
+    ///
+    /// 设置读请求处理函数，每次客户端读取时调用该函数计算返回数据(拉取模式)，无需注册共享内存区域。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - handler: 处理函数，返回的数据按请求长度截断，不足部分以 0 填充
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 操作失败
+    ///
+    /// `注：该函数基于 Srv_SetRWAreaCallback 实现，设置后所有读写请求都不再访问已注册的区域。拉取模式只提供数据，写请求会被拒绝，客户端收到 "Item not available" 错误，不会被静默丢弃。处理函数在 snap7 的线程中执行，不能 panic。`
+    ///
+    /// # Examples
+    /// ```ignore
+    /// let start = std::time::Instant::now();
+    /// server.set_pull_handler(Some(move |request: RwRequest| {
+    ///     let value = start.elapsed().as_secs_f32().sin().to_be_bytes();
+    ///     let mut data = vec![0u8; request.byte_size()];
+    ///     let size = data.len().min(value.len());
+    ///     data[..size].copy_from_slice(&value[..size]);
+    ///     data
+    /// })).unwrap();
+    /// ```
+    pub fn set_pull_handler<F>(&self, handler: Option<F>) -> Result<()>
+    where
+        F: FnMut(RwRequest) -> Vec<u8> + 'static,
+    {
+        let res = match handler {
+            Some(handler) => unsafe {
+                let data = Box::into_raw(Box::new(handler));
+                Srv_SetRWAreaCallback(
+                    self.handle,
+                    Some(call_pull_closure::<F>),
+                    data as *mut c_void,
+                )
+            },
+            None => unsafe {
+                Srv_SetRWAreaCallback(self.handle, None, std::ptr::null_mut() as *mut c_void)
+            },
+        };
+        if res == 0 {
+            return Ok(());
+        }
+        bail!(Snap7Error::server(res))
+    }
+
     ///
     /// 设置服务端对象在创建读取事件时要调用的用户回调。
     ///
@@ -528,7 +1032,7 @@ impl S7Server {
     where
         F: FnMut(*mut c_void, PSrvEvent, c_int) + 'static,
     {
-        if callback.is_some() {
+        if let Some(callback) = callback {
             unsafe {
                 let data = Box::into_raw(Box::new(callback));
                 let res = Srv_SetReadEventsCallback(
@@ -539,7 +1043,7 @@ impl S7Server {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::server(res))
             }
         } else {
             unsafe {
@@ -548,7 +1052,7 @@ impl S7Server {
                 if res == 0 {
                     return Ok(());
                 }
-                bail!("{}", Self::error_text(res))
+                bail!(Snap7Error::server(res))
             }
         }
     }
@@ -571,7 +1075,7 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
@@ -593,7 +1097,7 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
@@ -619,7 +1123,7 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
@@ -634,6 +1138,40 @@ impl S7Server {
         unsafe { Srv_ClearEvents(self.handle) == 0 }
     }
 
+    ///
+    /// 取出事件队列中的所有事件。
+    ///
+    /// **返回值:**
+    ///  - Ok: 按发生顺序排列的事件
+    ///  - Err: 操作失败
+    ///
+    pub fn drain_events(&self) -> Result<Vec<ServerEvent>> {
+        let mut events = Vec::new();
+        let mut event = TSrvEvent::default();
+        let mut evt_ready = 0;
+        loop {
+            self.pick_event(&mut event, &mut evt_ready)?;
+            if evt_ready == 0 {
+                return Ok(events);
+            }
+            events.push(event.into());
+        }
+    }
+
+    ///
+    /// 取出事件队列中的所有事件，并格式化为可读的文本行(时间、发送者、事件说明)。
+    ///
+    /// **返回值:**
+    ///  - 每个事件一行，队列为空或读取失败时为空
+    ///
+    pub fn events_summary(&self) -> Vec<String> {
+        self.drain_events()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|event| Self::event_text(&mut event.into()).unwrap_or_default())
+            .collect()
+    }
+
     ///
     /// 读取服务器状态、虚拟 CPU 状态和连接的客户端数量。
     ///
@@ -671,10 +1209,32 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
+    ///
+    /// 读取服务器状态、虚拟 CPU 状态和连接的客户端数量，并以结构体返回。
+    ///
+    /// **返回值:**
+    ///  - Ok: 服务端状态
+    ///  - Err: 操作失败
+    ///
+    pub fn status(&self) -> Result<ServerStatus> {
+        let mut server_status = 0;
+        let mut cpu_status = 0;
+        let mut client_count = 0;
+        self.get_status(&mut server_status, &mut cpu_status, &mut client_count)?;
+        let Some(server) = ServerState::from_raw(server_status) else {
+            bail!("unknown server status {}", server_status)
+        };
+        Ok(ServerStatus {
+            server,
+            cpu: CpuStatus::from_raw(cpu_status),
+            clients: client_count,
+        })
+    }
+
     ///
     /// 设置虚拟 CPU 状态。
     ///
@@ -695,7 +1255,7 @@ impl S7Server {
             if res == 0 {
                 return Ok(());
             }
-            bail!("{}", Self::error_text(res))
+            bail!(Snap7Error::server(res))
         }
     }
 
@@ -743,6 +1303,100 @@ impl S7Server {
     }
 }
 
+/// 共享内存区域锁守卫，由 S7Server::lock() 返回，Drop 时自动解锁。
+pub struct AreaLock<'a> {
+    server: &'a S7Server,
+    area_code: AreaCode,
+    index: u16,
+}
+
+impl Drop for AreaLock<'_> {
+    fn drop(&mut self) {
+        let _ = self.server.unlock_area(self.area_code, self.index);
+    }
+}
+
+/// 服务端分配的共享内存区，地址在其生命周期内保持不变。
+struct OwnedArea {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// 缓冲区只在区域锁内或由 snap7 服务端线程访问
+unsafe impl Send for OwnedArea {}
+unsafe impl Sync for OwnedArea {}
+
+impl OwnedArea {
+    fn new(size: usize) -> Self {
+        let buff = Box::into_raw(vec![0u8; size].into_boxed_slice());
+        OwnedArea {
+            ptr: buff as *mut u8,
+            len: size,
+        }
+    }
+
+    /// 调用者需保证访问期间没有其他可变引用(通常持有区域锁)
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn as_mut_slice(&self) -> &mut [u8] {
+        std::slice::from_raw_parts_mut(self.ptr, self.len)
+    }
+}
+
+impl Drop for OwnedArea {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(std::ptr::slice_from_raw_parts_mut(
+                self.ptr, self.len,
+            )));
+        }
+    }
+}
+
+/// 由 S7Server::register_struct() 返回的类型化 DB 句柄。
+pub struct StructHandle<'a, T: S7Struct> {
+    server: &'a S7Server,
+    index: u16,
+    area: Arc<OwnedArea>,
+    marker: PhantomData<T>,
+}
+
+impl<T: S7Struct> StructHandle<'_, T> {
+    /// DB 编号
+    pub fn db_index(&self) -> u16 {
+        self.index
+    }
+
+    ///
+    /// 在区域锁内从共享缓冲区解码当前值。
+    ///
+    /// **返回值:**
+    ///  - Ok: 当前值
+    ///  - Err: 加锁或解码失败
+    ///
+    pub fn get(&self) -> Result<T> {
+        let _lock = self.server.lock(AreaCode::S7AreaDB, self.index)?;
+        T::from_bytes(unsafe { self.area.as_mut_slice() }).map_err(Error::msg)
+    }
+
+    ///
+    /// 在区域锁内将值编码到共享缓冲区。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - value: 新值
+    ///
+    /// **返回值:**
+    ///  - Ok: 操作成功
+    ///  - Err: 加锁或编码失败
+    ///
+    pub fn set(&self, value: &T) -> Result<()> {
+        let _lock = self.server.lock(AreaCode::S7AreaDB, self.index)?;
+        value
+            .to_bytes(unsafe { self.area.as_mut_slice() })
+            .map_err(Error::msg)
+    }
+}
+
 unsafe extern "C" fn call_events_closure<F>(usr_ptr: *mut c_void, p_event: PSrvEvent, size: c_int)
 where
     F: FnMut(*mut c_void, PSrvEvent, c_int),
@@ -758,20 +1412,452 @@ unsafe extern "C" fn call_rw_area_closure<F>(
     operation: c_int,
     p_tag: PS7Tag,
     p_usr_data: *mut c_void,
-) where
+) -> c_int
+where
     F: FnMut(*mut c_void, c_int, c_int, PS7Tag, *mut c_void),
 {
     let callback_ptr = usr_ptr as *mut F;
     let callback = &mut *callback_ptr;
-    callback(usr_ptr, sender, operation, p_tag, p_usr_data)
+    callback(usr_ptr, sender, operation, p_tag, p_usr_data);
+    0
+}
+
+unsafe extern "C" fn call_pull_closure<F>(
+    usr_ptr: *mut c_void,
+    sender: c_int,
+    operation: c_int,
+    p_tag: PS7Tag,
+    p_usr_data: *mut c_void,
+) -> c_int
+where
+    F: FnMut(RwRequest) -> Vec<u8>,
+{
+    // 非 0 返回值使 snap7 以 "Item not available" 拒绝写请求
+    if operation != OPERATION_READ {
+        return 1;
+    }
+    let handler = &mut *(usr_ptr as *mut F);
+    let request = RwRequest::from_tag(sender, &*p_tag);
+    let data = handler(request);
+    let size = request.byte_size();
+    let copied = data.len().min(size);
+    let target = p_usr_data as *mut u8;
+    std::ptr::copy_nonoverlapping(data.as_ptr(), target, copied);
+    std::ptr::write_bytes(target.add(copied), 0, size - copied);
+    0
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        test_support::*,
+        utils::{getters, setters},
+    };
     use std::result::Result::Ok;
     use std::sync::{Arc, Mutex};
 
+    #[test]
+    fn test_register_area_index() {
+        let server = S7Server::create();
+        let mut buff = [0u8; 8];
+        let err = server
+            .register_area(AreaCode::S7AreaMK, 3, &mut buff)
+            .unwrap_err();
+        assert!(err.to_string().contains("S7AreaMK"));
+        assert!(server
+            .register_area(AreaCode::S7AreaMK, 0, &mut buff)
+            .is_ok());
+        assert!(server
+            .register_area(AreaCode::S7AreaDB, 3, &mut buff)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_forward_events_to() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (server, port) = start_server_with(|server| server.forward_events_to(tx).unwrap());
+
+        let client = connect_client(port);
+
+        let timeout = std::time::Duration::from_secs(3);
+        let started = rx.recv_timeout(timeout).unwrap();
+        assert_eq!(started.code, EVC_SERVER_STARTED);
+        let added = rx.recv_timeout(timeout).unwrap();
+        assert_eq!(added.code, EVC_CLIENT_ADDED);
+        assert!(added.time > 0);
+
+        client.disconnect().unwrap();
+        drop(rx);
+        // 接收端已释放，后续事件不应导致错误
+        server.stop().unwrap();
+    }
+
+    #[test]
+    fn test_forward_events_released() {
+        use std::sync::mpsc::RecvTimeoutError;
+
+        let timeout = std::time::Duration::from_secs(3);
+        let server = S7Server::create();
+
+        // 替换事件回调后旧的发送端被释放
+        let (tx, rx) = std::sync::mpsc::channel();
+        server.forward_events_to(tx).unwrap();
+        server
+            .set_events_callback(None::<fn(*mut c_void, PSrvEvent, c_int)>)
+            .unwrap();
+        assert_eq!(
+            rx.recv_timeout(timeout).unwrap_err(),
+            RecvTimeoutError::Disconnected
+        );
+
+        // 服务端释放后接收端的迭代结束
+        let (tx, rx) = std::sync::mpsc::channel();
+        server.forward_events_to(tx).unwrap();
+        let reader = std::thread::spawn(move || rx.into_iter().count());
+        drop(server);
+        let deadline = std::time::Instant::now() + timeout;
+        while !reader.is_finished() {
+            assert!(std::time::Instant::now() < deadline, "接收端未结束");
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        reader.join().unwrap();
+    }
+
+    #[test]
+    fn test_rejection_reason() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (server, port) = start_server_with(|server| {
+            server.set_max_clients(1).unwrap();
+            server.forward_events_to(tx).unwrap();
+        });
+
+        let connect = || {
+            let client = crate::client::S7Client::create();
+            client
+                .set_param(InternalParam::RemotePort, InternalParamValue::U16(port))
+                .unwrap();
+            let connected = client.connect_to("127.0.0.1", 0, 1);
+            (client, connected)
+        };
+        let (_first, connected) = connect();
+        connected.unwrap();
+        let (_second, connected) = connect();
+        assert!(connected.is_err());
+
+        let timeout = std::time::Duration::from_secs(3);
+        let rejected = loop {
+            let event = rx.recv_timeout(timeout).unwrap();
+            if event.code == EVC_CLIENT_REJECTED {
+                break event;
+            }
+            assert_eq!(event.rejection_reason(), None);
+        };
+        assert_eq!(
+            rejected.rejection_reason(),
+            Some(RejectionReason::MaxClientsReached)
+        );
+
+        server.stop().unwrap();
+    }
+
+    #[test]
+    fn test_control_event() {
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (server, port) = start_server_with(|server| server.forward_events_to(tx).unwrap());
+
+        let client = connect_client(port);
+        client.plc_stop().unwrap();
+
+        let timeout = std::time::Duration::from_secs(3);
+        let control = loop {
+            let event = rx.recv_timeout(timeout).unwrap();
+            if let Some(control) = event.control_event() {
+                break control;
+            }
+        };
+        assert_eq!(control.action, ControlAction::Stop);
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+    }
+
+    #[test]
+    fn test_events_summary() {
+        let (server, port) = start_server(&mut []);
+
+        let client = connect_client(port);
+        client.disconnect().unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+
+        let lines = server.events_summary();
+        assert!(lines[0].ends_with("Server started"), "{:?}", lines);
+        assert!(
+            lines[1].ends_with("[127.0.0.1] Client added"),
+            "{:?}",
+            lines
+        );
+        assert!(server.events_summary().is_empty());
+
+        server.stop().unwrap();
+    }
+
+    #[test]
+    fn test_events_handler_threaded() {
+        let port = next_test_port();
+        let (tx, rx) = std::sync::mpsc::channel();
+        let (release, gate) = std::sync::mpsc::channel::<()>();
+        let server = S7Server::create();
+        let mut db_buff = [0u8; 8];
+        server
+            .register_area(AreaCode::S7AreaDB, 1, &mut db_buff)
+            .unwrap();
+        server
+            .set_events_handler_threaded(
+                Some(move |event: ServerEvent| {
+                    // 处理线程一直阻塞，直到测试放行
+                    let _ = gate.recv();
+                    let _ = tx.send(event);
+                }),
+                1,
+                QueueFullPolicy::Block,
+            )
+            .unwrap();
+        server
+            .set_param(InternalParam::LocalPort, InternalParamValue::U16(port))
+            .unwrap();
+        server.start().unwrap();
+
+        let client = connect_client(port);
+
+        // 队列已满，写入事件使 snap7 线程阻塞在发送上，写请求因此在后台线程中执行
+        let writer = std::thread::spawn(move || {
+            let mut buff = [1u8; 8];
+            let _ = client.db_write(1, 0, 8, &mut buff);
+            client
+        });
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(!writer.is_finished());
+
+        // snap7 线程阻塞在发送上时关闭队列不应死锁
+        std::thread::scope(|s| {
+            let closer = s.spawn(|| {
+                server.set_events_handler_threaded(
+                    None::<fn(ServerEvent)>,
+                    1,
+                    QueueFullPolicy::Block,
+                )
+            });
+            let deadline = std::time::Instant::now() + Duration::from_secs(3);
+            while !closer.is_finished() && std::time::Instant::now() < deadline {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            let closed = closer.is_finished();
+            // 放行处理线程，让排队的事件和阻塞的写请求完成
+            drop(release);
+            assert!(closed);
+            closer.join().unwrap().unwrap();
+        });
+
+        let client = writer.join().unwrap();
+        let timeout = Duration::from_secs(3);
+        assert_eq!(rx.recv_timeout(timeout).unwrap().code, EVC_SERVER_STARTED);
+        assert_eq!(rx.recv_timeout(timeout).unwrap().code, EVC_CLIENT_ADDED);
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+    }
+
+    #[test]
+    fn test_max_clients() {
+        let server = S7Server::create();
+        server.set_max_clients(4).unwrap();
+        assert_eq!(server.max_clients().unwrap(), 4);
+        assert!(server.set_max_clients(0).is_err());
+        assert_eq!(server.max_clients().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_work_interval() {
+        let server = S7Server::create();
+        server
+            .set_work_interval(Duration::from_millis(250))
+            .unwrap();
+        assert_eq!(server.work_interval().unwrap(), Duration::from_millis(250));
+        assert!(server
+            .set_work_interval(Duration::from_secs(u64::MAX))
+            .is_err());
+    }
+
+    #[test]
+    fn test_lock_guard() {
+        let port = next_test_port();
+        let server = S7Server::create();
+        let mut db_buff = [0u8; 8];
+        let db_ptr = db_buff.as_mut_ptr();
+        server
+            .register_area(AreaCode::S7AreaDB, 1, &mut db_buff)
+            .unwrap();
+        server
+            .set_param(InternalParam::LocalPort, InternalParamValue::U16(port))
+            .unwrap();
+        server.start().unwrap();
+
+        {
+            let _guard = server.lock(AreaCode::S7AreaDB, 1).unwrap();
+            unsafe { *db_ptr = 0x5A };
+        }
+
+        // 若守卫未解锁，服务端读取会阻塞直至客户端超时
+        let client = connect_client(port);
+        let mut buff = [0u8; 1];
+        client.db_read(1, 0, 1, &mut buff).unwrap();
+        assert_eq!(buff[0], 0x5A);
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Recipe {
+        speed: f32,
+        count: i16,
+        running: bool,
+    }
+
+    impl S7Struct for Recipe {
+        const SIZE: usize = 7;
+
+        fn to_bytes(&self, buff: &mut [u8]) -> Result<(), String> {
+            setters::set_real(buff, 0, self.speed);
+            setters::set_int(buff, 4, self.count);
+            setters::set_bool(buff, 6, 1, self.running)
+        }
+
+        fn from_bytes(buff: &[u8]) -> Result<Self, String> {
+            Ok(Recipe {
+                speed: getters::get_real(buff, 0),
+                count: getters::get_int(buff, 4),
+                running: getters::get_bool(buff, 6, 1)?,
+            })
+        }
+    }
+
+    #[test]
+    fn test_register_struct() {
+        let port = next_test_port();
+        let server = S7Server::create();
+        let recipe = server
+            .register_struct(
+                3,
+                Recipe {
+                    speed: 1.5,
+                    count: 7,
+                    running: false,
+                },
+            )
+            .unwrap();
+        assert!(server
+            .register_struct(
+                3,
+                Recipe {
+                    speed: 0.0,
+                    count: 0,
+                    running: false
+                }
+            )
+            .is_err());
+        server
+            .set_param(InternalParam::LocalPort, InternalParamValue::U16(port))
+            .unwrap();
+        server.start().unwrap();
+
+        let client = connect_client(port);
+
+        recipe
+            .set(&Recipe {
+                speed: 42.25,
+                count: -3,
+                running: true,
+            })
+            .unwrap();
+        let mut buff = [0u8; Recipe::SIZE];
+        client
+            .db_read(3, 0, Recipe::SIZE as i32, &mut buff)
+            .unwrap();
+        assert_eq!(getters::get_real(&buff, 0), 42.25);
+        assert_eq!(getters::get_int(&buff, 4), -3);
+        assert!(getters::get_bool(&buff, 6, 1).unwrap());
+
+        let mut count = (-9i16).to_be_bytes();
+        client.db_write(3, 4, 2, &mut count).unwrap();
+        assert_eq!(
+            recipe.get().unwrap(),
+            Recipe {
+                speed: 42.25,
+                count: -9,
+                running: true,
+            }
+        );
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+    }
+
+    #[test]
+    fn test_status() {
+        let port = next_test_port();
+        let server = S7Server::create();
+        let status = server.status().unwrap();
+        assert_eq!(status.server, ServerState::Stopped);
+        assert_eq!(status.clients, 0);
+
+        server
+            .set_param(InternalParam::LocalPort, InternalParamValue::U16(port))
+            .unwrap();
+        server.start().unwrap();
+        let client = connect_client(port);
+
+        let status = server.status().unwrap();
+        assert_eq!(status.server, ServerState::Running);
+        assert_eq!(status.cpu, CpuStatus::Run);
+        assert_eq!(status.clients, 1);
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+    }
+
+    #[test]
+    fn test_pull_handler() {
+        let (server, port) = start_server_with(|server| {
+            let mut counter = 0u32;
+            server
+                .set_pull_handler(Some(move |request: RwRequest| {
+                    if request.area != Some(AreaTable::S7AreaDB) {
+                        return Vec::new();
+                    }
+                    counter += 1;
+                    let mut data = vec![0u8; request.byte_size()];
+                    data[..4].copy_from_slice(&counter.to_be_bytes());
+                    data
+                }))
+                .unwrap();
+        });
+        let client = connect_client(port);
+
+        let mut buff = [0u8; 4];
+        client.db_read(1, 0, 4, &mut buff).unwrap();
+        let first = u32::from_be_bytes(buff);
+        client.db_read(1, 0, 4, &mut buff).unwrap();
+        let second = u32::from_be_bytes(buff);
+        assert!(first > 0);
+        assert!(second > first);
+        assert!(client.db_write(1, 0, 4, &mut buff).is_err());
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+    }
+
     #[test]
     fn test_server() {
         let server = S7Server::create();
@@ -784,13 +1870,13 @@ mod tests {
             .register_area(AreaCode::S7AreaDB, 1, &mut db_buff)
             .is_ok());
         assert!(server
-            .register_area(AreaCode::S7AreaPA, 1, &mut ab_buff)
+            .register_area(AreaCode::S7AreaPA, 0, &mut ab_buff)
             .is_ok());
         assert!(server
-            .register_area(AreaCode::S7AreaPE, 1, &mut eb_buff)
+            .register_area(AreaCode::S7AreaPE, 0, &mut eb_buff)
             .is_ok());
         assert!(server
-            .register_area(AreaCode::S7AreaMK, 1, &mut mb_buff)
+            .register_area(AreaCode::S7AreaMK, 0, &mut mb_buff)
             .is_ok());
         // 过滤读和写
         assert!(server
@@ -887,4 +1973,90 @@ mod tests {
         );
         server.stop().unwrap();
     }
+
+    #[test]
+    fn test_params() {
+        let server = S7Server::create();
+        server
+            .set_param(InternalParam::LocalPort, InternalParamValue::U16(1102))
+            .unwrap();
+        server
+            .set_param(InternalParam::MaxClients, InternalParamValue::I32(4))
+            .unwrap();
+
+        let params = server.params().unwrap();
+        assert_eq!(params.local_port, 1102);
+        assert_eq!(params.max_clients, 4);
+    }
+
+    #[test]
+    fn test_snapshot_areas() {
+        let server = S7Server::create();
+        let mut borrowed = [0u8; 4];
+        server
+            .register_area(AreaCode::S7AreaDB, 8, &mut borrowed)
+            .unwrap();
+        server
+            .register_owned_area(AreaCode::S7AreaDB, 7, &[1, 2, 3, 4])
+            .unwrap();
+        server
+            .register_owned_area(AreaCode::S7AreaMK, 0, &[0xAA; 2])
+            .unwrap();
+        assert!(server
+            .register_owned_area(AreaCode::S7AreaMK, 1, &[0; 2])
+            .is_err());
+
+        // 调用者的缓冲区不在快照中
+        let snapshot = server.snapshot_areas();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[&(AreaCode::S7AreaDB, 7)], [1, 2, 3, 4]);
+        assert_eq!(snapshot[&(AreaCode::S7AreaMK, 0)], [0xAA, 0xAA]);
+
+        server.unregister_area(AreaCode::S7AreaMK, 0).unwrap();
+        let snapshot = server.snapshot_areas();
+        assert_eq!(snapshot.len(), 1);
+        assert!(snapshot.contains_key(&(AreaCode::S7AreaDB, 7)));
+    }
+
+    #[test]
+    fn test_restore_areas() {
+        let server = S7Server::create();
+        server
+            .register_owned_area(AreaCode::S7AreaDB, 7, &[1, 2, 3, 4])
+            .unwrap();
+        server
+            .register_owned_area(AreaCode::S7AreaMK, 0, &[0xAA; 2])
+            .unwrap();
+        let snapshot = server.snapshot_areas();
+
+        let mut borrowed = [0u8; 2];
+        server
+            .register_area(AreaCode::S7AreaDB, 8, &mut borrowed)
+            .unwrap();
+        let mut foreign = snapshot.clone();
+        foreign.insert((AreaCode::S7AreaDB, 8), vec![1, 1]);
+        assert!(server.restore_areas(&foreign, true).is_err());
+        server.restore_areas(&foreign, false).unwrap();
+        assert_eq!(borrowed, [0, 0]);
+
+        let zeroed = snapshot
+            .iter()
+            .map(|(&key, data)| (key, vec![0; data.len()]))
+            .collect();
+        server.restore_areas(&zeroed, true).unwrap();
+        assert_ne!(server.snapshot_areas(), snapshot);
+        server.restore_areas(&snapshot, true).unwrap();
+        assert_eq!(server.snapshot_areas(), snapshot);
+
+        // 大小不一致: 严格模式报错且不写入，非严格模式跳过
+        let mut mismatched = snapshot.clone();
+        mismatched.insert((AreaCode::S7AreaDB, 7), vec![9; 5]);
+        mismatched.insert((AreaCode::S7AreaMK, 0), vec![5, 5]);
+        assert!(server.restore_areas(&mismatched, true).is_err());
+        assert_eq!(server.snapshot_areas(), snapshot);
+        server.restore_areas(&mismatched, false).unwrap();
+        let restored = server.snapshot_areas();
+        assert_eq!(restored[&(AreaCode::S7AreaDB, 7)], [1, 2, 3, 4]);
+        assert_eq!(restored[&(AreaCode::S7AreaMK, 0)], [5, 5]);
+    }
 }
@@ -0,0 +1,152 @@
+//
+// stream.rs
+// Copyright (C) 2021 gmg137 <gmg137 AT live.com>
+// snap7-rs is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//          http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+// EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+// MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+//
+use crate::client::S7Client;
+use anyhow::*;
+use std::io;
+
+/// 以字节流方式顺序读写 DB 的适配器，实现 `std::io::Read` 和 `std::io::Write`
+///
+/// 每次读写都通过 db_read()/db_write() 完成并推进当前偏移，到达 DB 末尾时读取返回 EOF，
+/// 写入返回 0 字节(write_all 因此报告 `WriteZero` 错误)。
+///
+/// # Examples
+/// ```ignore
+/// use rust_snap7::{DbStream, S7Client};
+/// use std::io::Read;
+///
+/// let client = S7Client::create();
+/// client.connect_to("192.168.1.123", 0, 1).unwrap();
+/// let mut stream = DbStream::new(&client, 1, 0).unwrap();
+/// let mut data = Vec::new();
+/// stream.read_to_end(&mut data).unwrap();
+/// ```
+pub struct DbStream<'a> {
+    client: &'a S7Client,
+    db_number: i32,
+    position: usize,
+    end: usize,
+}
+
+impl<'a> DbStream<'a> {
+    ///
+    /// 创建从 DB 的 start 字节处开始的字节流，通过 db_size() 确定 DB 末尾。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - client: 已连接的客户端
+    ///  - db_number: 数据块(DB)编号
+    ///  - start: 起始字节地址
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: DbStream
+    ///  - Err: 读取 DB 大小失败，或起始地址超出 DB 大小
+    ///
+    pub fn new(client: &'a S7Client, db_number: i32, start: usize) -> Result<Self> {
+        let end = client.db_size(db_number)?;
+        if start > end {
+            bail!(
+                "start offset {} exceeds the size {} of DB{}",
+                start,
+                end,
+                db_number
+            );
+        }
+        Ok(DbStream {
+            client,
+            db_number,
+            position: start,
+            end,
+        })
+    }
+
+    /// 当前字节偏移
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// 距离 DB 末尾的剩余字节数
+    pub fn remaining(&self) -> usize {
+        self.end - self.position
+    }
+}
+
+impl io::Read for DbStream<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.remaining());
+        if len == 0 {
+            return io::Result::Ok(0);
+        }
+        self.client
+            .db_read(
+                self.db_number,
+                self.position as i32,
+                len as i32,
+                &mut buf[..len],
+            )
+            .map_err(io::Error::other)?;
+        self.position += len;
+        io::Result::Ok(len)
+    }
+}
+
+impl io::Write for DbStream<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.remaining());
+        if len == 0 {
+            return io::Result::Ok(0);
+        }
+        let mut data = buf[..len].to_vec();
+        self.client
+            .db_write(self.db_number, self.position as i32, len as i32, &mut data)
+            .map_err(io::Error::other)?;
+        self.position += len;
+        io::Result::Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::Result::Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{model::*, test_support::*};
+    use std::io::{Read, Write};
+
+    #[test]
+    fn test_read_to_end_and_write() {
+        let mut db_buff: Vec<u8> = (0..32).collect();
+        let (server, port) = start_server(&mut [(AreaCode::S7AreaDB, 4, &mut db_buff)]);
+        let client = connect_client(port);
+
+        let mut stream = DbStream::new(&client, 4, 20).unwrap();
+        let mut data = Vec::new();
+        assert_eq!(stream.read_to_end(&mut data).unwrap(), 12);
+        assert_eq!(data, (20..32).collect::<Vec<u8>>());
+        assert_eq!(stream.position(), 32);
+
+        let mut stream = DbStream::new(&client, 4, 28).unwrap();
+        stream.write_all(&[0xAA, 0xBB]).unwrap();
+        assert_eq!(stream.remaining(), 2);
+        let err = stream.write_all(&[1, 2, 3]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::WriteZero);
+
+        assert!(DbStream::new(&client, 4, 33).is_err());
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+        assert_eq!(db_buff[28..32], [0xAA, 0xBB, 1, 2]);
+    }
+}
@@ -0,0 +1,57 @@
+//
+// test_support.rs
+// Copyright (C) 2021 gmg137 <gmg137 AT live.com>
+// snap7-rs is licensed under Mulan PSL v2.
+// You can use this software according to the terms and conditions of the Mulan PSL v2.
+// You may obtain a copy of Mulan PSL v2 at:
+//          http://license.coscl.org.cn/MulanPSL2
+// THIS SOFTWARE IS PROVIDED ON AN "AS IS" BASIS, WITHOUT WARRANTIES OF ANY KIND,
+// EITHER EXPRESS OR IMPLIED, INCLUDING BUT NOT LIMITED TO NON-INFRINGEMENT,
+// MERCHANTABILITY OR FIT FOR A PARTICULAR PURPOSE.
+// See the Mulan PSL v2 for more details.
+//
+use crate::{client::S7Client, model::*, server::S7Server};
+use std::sync::atomic::{AtomicU16, Ordering};
+
+/// 测试用的本地端口分配器，并行运行的测试各自绑定不同的端口。
+pub(crate) fn next_test_port() -> u16 {
+    static NEXT_PORT: AtomicU16 = AtomicU16::new(7900);
+    NEXT_PORT.fetch_add(1, Ordering::SeqCst)
+}
+
+/// 在新分配的端口上启动本地服务端，configure 在启动前调用，返回服务端及其端口。
+pub(crate) fn start_server_with(configure: impl FnOnce(&S7Server)) -> (S7Server, u16) {
+    let port = next_test_port();
+    let server = S7Server::create();
+    configure(&server);
+    server
+        .set_param(InternalParam::LocalPort, InternalParamValue::U16(port))
+        .unwrap();
+    server.start().unwrap();
+    (server, port)
+}
+
+/// 启动注册了给定共享区的本地服务端，返回服务端及其端口。
+pub(crate) fn start_server(areas: &mut [(AreaCode, u16, &mut [u8])]) -> (S7Server, u16) {
+    start_server_with(|server| {
+        for (area_code, index, buff) in areas.iter_mut() {
+            server.register_area(*area_code, *index, buff).unwrap();
+        }
+    })
+}
+
+/// 创建连接到本地指定端口的客户端。
+pub(crate) fn connect_client(port: u16) -> S7Client {
+    let client = S7Client::create();
+    client
+        .set_param(InternalParam::RemotePort, InternalParamValue::U16(port))
+        .unwrap();
+    client.connect_to("127.0.0.1", 0, 1).unwrap();
+    client
+}
+
+/// 启动以 db_buff 作为 DB1 的本地服务端并连接客户端。
+pub(crate) fn connect_local(db_buff: &mut [u8]) -> (S7Server, S7Client) {
+    let (server, port) = start_server(&mut [(AreaCode::S7AreaDB, 1, db_buff)]);
+    (server, connect_client(port))
+}
@@ -0,0 +1,118 @@
+use super::layout::S7Type;
+use crate::model::AreaTable;
+use std::str::FromStr;
+
+/// S7 绝对地址，如 `DB1.DBD4`、`DB1.DBX0.1`、`MW10`、`I0.0`
+///
+/// 地址只描述访问宽度(X/B/W/D)，解析后的类型为 Bool/Byte/Word/DWord，
+/// 需要按 REAL、INT 等解码时使用 `with_type` 替换。
+///
+/// # Examples
+/// ```
+/// use rust_snap7::utils::address::TagAddress;
+/// use rust_snap7::utils::layout::S7Type;
+///
+/// let addr: TagAddress = "DB1.DBD4".parse().unwrap();
+/// assert_eq!(addr.db_number, 1);
+/// assert_eq!(addr.byte_offset, 4);
+/// assert_eq!(addr.with_type(S7Type::Real).s7_type, S7Type::Real);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TagAddress {
+    /// 区域
+    pub area: AreaTable,
+    /// 数据块(DB)编号，区域不为 S7AreaDB 时为 0
+    pub db_number: u16,
+    /// 起始字节地址
+    pub byte_offset: usize,
+    /// 数据类型
+    pub s7_type: S7Type,
+}
+
+impl TagAddress {
+    /// 以相同的地址按另一种类型访问
+    pub fn with_type(self, s7_type: S7Type) -> Self {
+        TagAddress { s7_type, ..self }
+    }
+}
+
+impl FromStr for TagAddress {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let upper = s.trim().to_ascii_uppercase();
+        let invalid = || format!("invalid S7 address: {}", s);
+
+        let (area, db_number, rest) = if let Some(db) = upper.strip_prefix("DB") {
+            let (number, rest) = db.split_once(".DB").ok_or_else(invalid)?;
+            let number = number.parse::<u16>().map_err(|_| invalid())?;
+            (AreaTable::S7AreaDB, number, rest)
+        } else {
+            let area = match upper.chars().next() {
+                Some('M') => AreaTable::S7AreaMK,
+                Some('I') | Some('E') => AreaTable::S7AreaPE,
+                Some('Q') | Some('A') => AreaTable::S7AreaPA,
+                _ => return Err(invalid()),
+            };
+            (area, 0, &upper[1..])
+        };
+
+        let (width, rest) = match rest.chars().next() {
+            Some(c @ ('X' | 'B' | 'W' | 'D')) => (c, &rest[1..]),
+            Some(c) if c.is_ascii_digit() && area != AreaTable::S7AreaDB => ('X', rest),
+            _ => return Err(invalid()),
+        };
+        let (offset, bit) = match rest.split_once('.') {
+            Some((offset, bit)) => (offset, Some(bit)),
+            None => (rest, None),
+        };
+        let byte_offset = offset.parse::<usize>().map_err(|_| invalid())?;
+        let s7_type = match (width, bit) {
+            ('X', Some(bit)) => match bit.parse::<usize>() {
+                Ok(bit) if bit < 8 => S7Type::Bool(bit),
+                _ => return Err(invalid()),
+            },
+            ('B', None) => S7Type::Byte,
+            ('W', None) => S7Type::Word,
+            ('D', None) => S7Type::DWord,
+            _ => return Err(invalid()),
+        };
+
+        Ok(TagAddress {
+            area,
+            db_number,
+            byte_offset,
+            s7_type,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let parse = |s: &str| s.parse::<TagAddress>();
+        assert_eq!(
+            parse("DB10.DBX3.5").unwrap(),
+            TagAddress {
+                area: AreaTable::S7AreaDB,
+                db_number: 10,
+                byte_offset: 3,
+                s7_type: S7Type::Bool(5),
+            }
+        );
+        assert_eq!(parse("db1.dbw2").unwrap().s7_type, S7Type::Word);
+        assert_eq!(parse("MD8").unwrap().area, AreaTable::S7AreaMK);
+        assert_eq!(parse("M0.7").unwrap().s7_type, S7Type::Bool(7));
+        assert_eq!(parse("E1.0").unwrap().area, AreaTable::S7AreaPE);
+        assert_eq!(parse("QB4").unwrap().area, AreaTable::S7AreaPA);
+
+        assert!(parse("DB1.DBX0.8").is_err());
+        assert!(parse("DB1.DBW2.1").is_err());
+        assert!(parse("DB1.DBX0").is_err());
+        assert!(parse("DB1.4").is_err());
+        assert!(parse("T5").is_err());
+    }
+}
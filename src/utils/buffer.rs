@@ -0,0 +1,227 @@
+use super::{getters, setters};
+use anyhow::*;
+
+/// 绑定到字节缓冲区的 S7 数据读写器
+///
+/// 包装 `getters`/`setters` 中的函数，读写前检查 `byte_index + 类型长度` 是否超出缓冲区，
+/// 越界时返回错误而不是 panic。
+///
+/// # Examples
+/// ```
+/// use rust_snap7::utils::buffer::S7Buffer;
+///
+/// let mut data = [0u8; 8];
+/// let mut buffer = S7Buffer::new(&mut data);
+/// buffer.write_real(0, 21.5).unwrap();
+/// assert_eq!(buffer.read_real(0).unwrap(), 21.5);
+/// assert!(buffer.read_real(6).is_err());
+/// ```
+#[derive(Debug)]
+pub struct S7Buffer<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> S7Buffer<'a> {
+    /// 包装一个字节缓冲区
+    pub fn new(data: &'a mut [u8]) -> Self {
+        S7Buffer { data }
+    }
+
+    /// 缓冲区长度
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// 缓冲区是否为空
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// 底层字节
+    pub fn as_slice(&self) -> &[u8] {
+        self.data
+    }
+
+    /// 可写的底层字节，可直接传给 db_read()/db_write()
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        self.data
+    }
+
+    fn check(&self, byte_index: usize, size: usize) -> Result<()> {
+        if byte_index
+            .checked_add(size)
+            .is_none_or(|end| end > self.data.len())
+        {
+            bail!(
+                "{} bytes at offset {} exceed the buffer length {}",
+                size,
+                byte_index,
+                self.data.len()
+            );
+        }
+        Ok(())
+    }
+
+    /// 读取 BOOL
+    pub fn read_bool(&self, byte_index: usize, bool_index: usize) -> Result<bool> {
+        self.check(byte_index, 1)?;
+        getters::get_bool(self.data, byte_index, bool_index).map_err(Error::msg)
+    }
+
+    /// 写入 BOOL
+    pub fn write_bool(&mut self, byte_index: usize, bool_index: usize, value: bool) -> Result<()> {
+        self.check(byte_index, 1)?;
+        setters::set_bool(self.data, byte_index, bool_index, value).map_err(Error::msg)
+    }
+
+    /// 读取 BYTE
+    pub fn read_byte(&self, byte_index: usize) -> Result<u8> {
+        self.check(byte_index, 1)?;
+        Ok(getters::get_byte(self.data, byte_index))
+    }
+
+    /// 写入 BYTE
+    pub fn write_byte(&mut self, byte_index: usize, value: u8) -> Result<()> {
+        self.check(byte_index, 1)?;
+        setters::set_byte(self.data, byte_index, value);
+        Ok(())
+    }
+
+    /// 读取 WORD
+    pub fn read_word(&self, byte_index: usize) -> Result<u16> {
+        self.check(byte_index, 2)?;
+        Ok(getters::get_word(self.data, byte_index))
+    }
+
+    /// 写入 WORD
+    pub fn write_word(&mut self, byte_index: usize, value: u16) -> Result<()> {
+        self.check(byte_index, 2)?;
+        setters::set_word(self.data, byte_index, value);
+        Ok(())
+    }
+
+    /// 读取 INT
+    pub fn read_int(&self, byte_index: usize) -> Result<i16> {
+        self.check(byte_index, 2)?;
+        Ok(getters::get_int(self.data, byte_index))
+    }
+
+    /// 写入 INT
+    pub fn write_int(&mut self, byte_index: usize, value: i16) -> Result<()> {
+        self.check(byte_index, 2)?;
+        setters::set_int(self.data, byte_index, value);
+        Ok(())
+    }
+
+    /// 读取 DWORD
+    pub fn read_dword(&self, byte_index: usize) -> Result<u32> {
+        self.check(byte_index, 4)?;
+        Ok(getters::get_dword(self.data, byte_index))
+    }
+
+    /// 写入 DWORD
+    pub fn write_dword(&mut self, byte_index: usize, value: u32) -> Result<()> {
+        self.check(byte_index, 4)?;
+        setters::set_dword(self.data, byte_index, value);
+        Ok(())
+    }
+
+    /// 读取 DINT
+    pub fn read_dint(&self, byte_index: usize) -> Result<i32> {
+        self.check(byte_index, 4)?;
+        Ok(getters::get_dint(self.data, byte_index))
+    }
+
+    /// 写入 DINT
+    pub fn write_dint(&mut self, byte_index: usize, value: i32) -> Result<()> {
+        self.check(byte_index, 4)?;
+        setters::set_dint(self.data, byte_index, value);
+        Ok(())
+    }
+
+    /// 读取 REAL
+    pub fn read_real(&self, byte_index: usize) -> Result<f32> {
+        self.check(byte_index, 4)?;
+        Ok(getters::get_real(self.data, byte_index))
+    }
+
+    /// 写入 REAL
+    pub fn write_real(&mut self, byte_index: usize, value: f32) -> Result<()> {
+        self.check(byte_index, 4)?;
+        setters::set_real(self.data, byte_index, value);
+        Ok(())
+    }
+
+    /// 读取 LREAL
+    pub fn read_lreal(&self, byte_index: usize) -> Result<f64> {
+        self.check(byte_index, 8)?;
+        Ok(getters::get_lreal(self.data, byte_index))
+    }
+
+    /// 写入 LREAL
+    pub fn write_lreal(&mut self, byte_index: usize, value: f64) -> Result<()> {
+        self.check(byte_index, 8)?;
+        setters::set_lreal(self.data, byte_index, value);
+        Ok(())
+    }
+
+    /// 读取 STRING，长度由头部的最大长度和实际长度决定
+    pub fn read_string(&self, byte_index: usize) -> Result<String> {
+        self.check(byte_index, 2)?;
+        self.check(byte_index, self.data[byte_index] as usize + 2)?;
+        getters::get_string(self.data, byte_index).map_err(Error::msg)
+    }
+
+    /// 写入最大长度为 max_size 的 STRING，占用 max_size + 2 字节
+    pub fn write_string(&mut self, byte_index: usize, value: &str, max_size: u8) -> Result<()> {
+        self.check(byte_index, max_size as usize + 2)?;
+        setters::set_string(self.data, byte_index, value, max_size as usize).map_err(Error::msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let mut data = [0u8; 32];
+        let mut buffer = S7Buffer::new(&mut data);
+        buffer.write_bool(0, 3, true).unwrap();
+        buffer.write_byte(1, 0xAB).unwrap();
+        buffer.write_word(2, 0xBEEF).unwrap();
+        buffer.write_int(4, -1234).unwrap();
+        buffer.write_dint(6, -123_456).unwrap();
+        buffer.write_real(10, 21.5).unwrap();
+        buffer.write_string(14, "snap7", 10).unwrap();
+
+        assert!(buffer.read_bool(0, 3).unwrap());
+        assert!(!buffer.read_bool(0, 2).unwrap());
+        assert_eq!(buffer.read_byte(1).unwrap(), 0xAB);
+        assert_eq!(buffer.read_word(2).unwrap(), 0xBEEF);
+        assert_eq!(buffer.read_int(4).unwrap(), -1234);
+        assert_eq!(buffer.read_dint(6).unwrap(), -123_456);
+        assert_eq!(buffer.read_real(10).unwrap(), 21.5);
+        assert_eq!(buffer.read_string(14).unwrap(), "snap7");
+        assert_eq!(data[14..21], [10, 5, b's', b'n', b'a', b'p', b'7']);
+    }
+
+    #[test]
+    fn test_out_of_bounds() {
+        let mut data = [0u8; 4];
+        let mut buffer = S7Buffer::new(&mut data);
+        assert!(buffer.read_bool(4, 0).is_err());
+        assert!(buffer.write_byte(4, 1).is_err());
+        assert!(buffer.read_word(3).is_err());
+        assert!(buffer.write_int(3, 1).is_err());
+        assert!(buffer.read_dint(1).is_err());
+        assert!(buffer.write_real(1, 1.0).is_err());
+        assert!(buffer.read_real(usize::MAX).is_err());
+        assert!(buffer.write_string(0, "abc", 3).is_err());
+        assert!(buffer.write_string(0, "abc", 2).is_err());
+        buffer.write_string(0, "ab", 2).unwrap();
+        assert_eq!(buffer.read_string(0).unwrap(), "ab");
+        buffer.as_mut_slice()[0] = 10;
+        assert!(buffer.read_string(0).is_err());
+    }
+}
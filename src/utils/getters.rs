@@ -3,7 +3,7 @@ use std::convert::TryInto;
 use std::time::Duration;
 
 pub fn get_bool(bytearray: &[u8], byte_index: usize, bool_index: usize) -> Result<bool, String> {
-    if bytearray.len() < byte_index + 2 || bool_index > 7 {
+    if bytearray.len() < byte_index + 1 || bool_index > 7 {
         return Err("Buffer has no enough data to decoding".to_string());
     }
     let index_value = 1 << bool_index;
@@ -12,6 +12,24 @@ pub fn get_bool(bytearray: &[u8], byte_index: usize, bool_index: usize) -> Resul
     Ok(current_value == index_value)
 }
 
+pub fn get_bool_array(
+    bytearray: &[u8],
+    byte_index: usize,
+    bool_index: usize,
+    count: usize,
+) -> Result<Vec<bool>, String> {
+    if bool_index > 7 {
+        return Err(format!("bool_index {} out of range", bool_index));
+    }
+    let first_bit = byte_index * 8 + bool_index;
+    if bytearray.len() * 8 < first_bit + count {
+        return Err("Buffer has no enough data to decoding".to_string());
+    }
+    Ok((first_bit..first_bit + count)
+        .map(|bit| bytearray[bit / 8] & (1 << (bit % 8)) != 0)
+        .collect())
+}
+
 pub fn get_byte(bytearray: &[u8], byte_index: usize) -> u8 {
     bytearray[byte_index]
 }
@@ -21,6 +39,15 @@ pub fn get_word(bytearray: &[u8], byte_index: usize) -> u16 {
     u16::from_be_bytes(data)
 }
 
+/// 将大端 WORD 解码为 16 个位，下标 i 对应数值的第 i 位(0 为最低位)。
+///
+/// S7 中 WORD 的高字节在前，因此下标 0..8 对应 `byte_index + 1` 字节的 X0..X7，
+/// 下标 8..16 对应 `byte_index` 字节的 X0..X7。
+pub fn get_word_bits(bytearray: &[u8], byte_index: usize) -> [bool; 16] {
+    let word = get_word(bytearray, byte_index);
+    std::array::from_fn(|bit| word & (1 << bit) != 0)
+}
+
 pub fn get_int(bytearray: &[u8], byte_index: usize) -> i16 {
     let data: [u8; 2] = bytearray[byte_index..byte_index + 2].try_into().unwrap();
     i16::from_be_bytes(data)
@@ -35,6 +62,31 @@ pub fn get_real(bytearray: &[u8], byte_index: usize) -> f32 {
     f32::from_bits(u32::from_be_bytes(data))
 }
 
+/// get_real_auto() 判定的 REAL 字节序
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteOrder {
+    /// 大端，S7 的标准字节序
+    BigEndian,
+    /// 小端
+    LittleEndian,
+}
+
+/// 字节序未知时读取 REAL，同时返回采用的字节序。
+///
+/// 先按大端解码；若结果为 NaN 或非规格化数而小端解码为正常的有限值，则采用小端结果。
+pub fn get_real_auto(bytearray: &[u8], byte_index: usize) -> Result<(f32, ByteOrder), String> {
+    if bytearray.len() < byte_index + 4 {
+        return Err("Buffer has no enough data to decoding".to_string());
+    }
+    let data: [u8; 4] = bytearray[byte_index..byte_index + 4].try_into().unwrap();
+    let big = f32::from_bits(u32::from_be_bytes(data));
+    let little = f32::from_bits(u32::from_le_bytes(data));
+    if (big.is_nan() || big.is_subnormal()) && little.is_normal() {
+        return Ok((little, ByteOrder::LittleEndian));
+    }
+    Ok((big, ByteOrder::BigEndian))
+}
+
 pub fn get_fstring(
     bytearray: &[u8],
     byte_index: usize,
@@ -42,7 +94,7 @@ pub fn get_fstring(
     remove_padding: bool,
 ) -> String {
     let data = &bytearray[byte_index..byte_index + max_length];
-    let string = String::from_utf8(data.to_vec()).unwrap();
+    let string = String::from_utf8_lossy(data).into_owned();
 
     if remove_padding {
         string.trim_end().to_string()
@@ -51,6 +103,26 @@ pub fn get_fstring(
     }
 }
 
+pub fn get_char(bytearray: &[u8], byte_index: usize) -> Result<char, String> {
+    let Some(&byte) = bytearray.get(byte_index) else {
+        return Err("Buffer has no enough data to decoding".to_string());
+    };
+    if !byte.is_ascii() {
+        return Err(format!("Non-ASCII byte: {:#04x}", byte));
+    }
+    Ok(byte as char)
+}
+
+pub fn get_char_array(bytearray: &[u8], byte_index: usize, count: usize) -> Result<String, String> {
+    let Some(data) = bytearray.get(byte_index..byte_index + count) else {
+        return Err("Buffer has no enough data to decoding".to_string());
+    };
+    if let Some(byte) = data.iter().find(|byte| !byte.is_ascii()) {
+        return Err(format!("Non-ASCII byte: {:#04x}", byte));
+    }
+    Ok(data.iter().map(|&b| b as char).collect())
+}
+
 pub fn get_string(bytearray: &[u8], byte_index: usize) -> Result<String, String> {
     let max_string_size = bytearray[byte_index] as usize;
     let str_length = bytearray[byte_index + 1] as usize;
@@ -68,6 +140,24 @@ pub fn get_string(bytearray: &[u8], byte_index: usize) -> Result<String, String>
     Ok(str_data)
 }
 
+/// 借用 S7 STRING 的有效内容字节，不分配内存也不解码。
+///
+/// 头部长度不一致或缓冲区不足时返回空切片。
+pub fn get_string_bytes(bytearray: &[u8], byte_index: usize) -> &[u8] {
+    let (Some(&max_string_size), Some(&str_length)) =
+        (bytearray.get(byte_index), bytearray.get(byte_index + 1))
+    else {
+        return &[];
+    };
+    if str_length > max_string_size || max_string_size > 254 {
+        return &[];
+    }
+    let start = byte_index + 2;
+    bytearray
+        .get(start..start + str_length as usize)
+        .unwrap_or(&[])
+}
+
 pub fn get_dword(bytearray: &[u8], byte_index: usize) -> u32 {
     let data: [u8; 4] = bytearray[byte_index..byte_index + 4].try_into().unwrap();
     u32::from_be_bytes(data)
@@ -83,29 +173,44 @@ pub fn get_udint(bytearray: &[u8], byte_index: usize) -> u32 {
 }
 
 pub fn get_s5time(bytearray: &[u8], byte_index: usize) -> String {
-    let data_bytearray = &bytearray[byte_index..byte_index + 2];
-    let s5time_data_int_like = format!("{:02X}{:02X}", data_bytearray[0], data_bytearray[1]);
-
-    let time_base = match &s5time_data_int_like[0..1] {
-        "0" => 10,
-        "1" => 100,
-        "2" => 1000,
-        "3" => 10000,
-        _ => panic!("This value should not be greater than 3"),
+    let s5time = get_s5time_duration(bytearray, byte_index).unwrap();
+    format!("{:?}", s5time)
+}
+
+pub fn get_s5time_duration(bytearray: &[u8], byte_index: usize) -> Result<Duration, String> {
+    if bytearray.len() < byte_index + 2 {
+        return Err("Buffer has no enough data to decoding".to_string());
+    }
+    let value = u16::from_be_bytes([bytearray[byte_index], bytearray[byte_index + 1]]);
+    let time_base_ms = match value >> 12 {
+        0 => 10,
+        1 => 100,
+        2 => 1000,
+        3 => 10000,
+        _ => return Err("S5TIME time base should not be greater than 3".to_string()),
     };
+    let bcd = bcd_to_u16(value & 0x0FFF)?;
+    Ok(Duration::from_millis(time_base_ms * bcd as u64))
+}
 
-    let mut s5time_bcd: i32 = 0;
+pub fn get_counter(bytearray: &[u8], byte_index: usize) -> Result<u16, String> {
+    if bytearray.len() < byte_index + 2 {
+        return Err("Buffer has no enough data to decoding".to_string());
+    }
+    let value = u16::from_be_bytes([bytearray[byte_index], bytearray[byte_index + 1]]);
+    bcd_to_u16(value & 0x0FFF)
+}
 
-    for (i, digit) in s5time_data_int_like.chars().enumerate() {
-        if i > 0 {
-            s5time_bcd *= 10;
-            s5time_bcd += digit.to_digit(10).unwrap() as i32;
+fn bcd_to_u16(value: u16) -> Result<u16, String> {
+    let mut result = 0;
+    for shift in [8, 4, 0] {
+        let digit = (value >> shift) & 0xF;
+        if digit > 9 {
+            return Err(format!("Invalid BCD value: {:#06x}", value));
         }
+        result = result * 10 + digit;
     }
-
-    let s5time_microseconds = time_base * s5time_bcd;
-    let s5time = Duration::from_micros(s5time_microseconds as u64 * 1000);
-    format!("{:?}", s5time)
+    Ok(result)
 }
 
 pub fn get_dt(bytearray: &[u8], byte_index: usize) -> String {
@@ -113,9 +218,20 @@ pub fn get_dt(bytearray: &[u8], byte_index: usize) -> String {
 }
 
 pub fn get_date_time_object(bytearray: &[u8], byte_index: usize) -> DateTime<Utc> {
+    try_get_date_time_object(bytearray, byte_index).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// 解码 DATE_AND_TIME，缓冲区不足或日期时间无效时返回错误
+pub fn try_get_date_time_object(
+    bytearray: &[u8],
+    byte_index: usize,
+) -> Result<DateTime<Utc>, String> {
     fn bcd_to_byte(byte: u8) -> u8 {
         (byte >> 4) * 10 + (byte & 0xF)
     }
+    if bytearray.len() < byte_index + 8 {
+        return Err("Buffer has no enough data to decoding".to_string());
+    }
     let year = bcd_to_byte(bytearray[byte_index]) as i32;
     let year = if year < 90 { 2000 + year } else { 1900 + year };
     let month = bcd_to_byte(bytearray[byte_index + 1]);
@@ -128,22 +244,32 @@ pub fn get_date_time_object(bytearray: &[u8], byte_index: usize) -> DateTime<Utc
         * 1000;
 
     NaiveDate::from_ymd_opt(year, month.into(), day.into())
-        .expect("failed to parse date")
+        .ok_or_else(|| "failed to parse date".to_string())?
         .and_hms_micro_opt(hour.into(), min.into(), sec.into(), microsec)
-        .expect("failed to parse time")
-        .and_utc()
+        .ok_or_else(|| "failed to parse time".to_string())
+        .map(|value| value.and_utc())
+}
+
+pub fn get_dt_array(
+    bytearray: &[u8],
+    byte_index: usize,
+    count: usize,
+) -> Result<Vec<DateTime<Utc>>, String> {
+    if bytearray.len() < byte_index + count * 8 {
+        return Err("Buffer has no enough data to decoding".to_string());
+    }
+    (0..count)
+        .map(|i| try_get_date_time_object(bytearray, byte_index + i * 8))
+        .collect()
 }
 
 pub fn get_time(bytearray: &[u8], byte_index: usize) -> String {
     let data_bytearray = &bytearray[byte_index..byte_index + 4];
-    let mut val = i32::from_be_bytes(data_bytearray.try_into().unwrap());
+    let val = i32::from_be_bytes(data_bytearray.try_into().unwrap());
 
-    let sign_str = if val < 0 {
-        val = -val;
-        "-"
-    } else {
-        ""
-    };
+    let sign_str = if val < 0 { "-" } else { "" };
+    // unsigned_abs 避免 i32::MIN 取反溢出
+    let val = val.unsigned_abs();
 
     let milli_seconds = val % 1000;
     let seconds = (val / 1000) % 60;
@@ -153,15 +279,20 @@ pub fn get_time(bytearray: &[u8], byte_index: usize) -> String {
 
     format!(
         "{}{}:{}:{}:{}.{}",
-        sign_str,
-        days,
-        hours % 24,
-        minutes % 60,
-        seconds % 60,
-        milli_seconds
+        sign_str, days, hours, minutes, seconds, milli_seconds
     )
 }
 
+pub fn get_time_duration(bytearray: &[u8], byte_index: usize) -> Result<chrono::Duration, String> {
+    if bytearray.len() < byte_index + 4 {
+        return Err("Buffer has no enough data to decoding".to_string());
+    }
+    let data: [u8; 4] = bytearray[byte_index..byte_index + 4].try_into().unwrap();
+    Ok(chrono::Duration::milliseconds(
+        i32::from_be_bytes(data) as i64
+    ))
+}
+
 pub fn get_usint(bytearray: &[u8], byte_index: usize) -> u8 {
     bytearray[byte_index]
 }
@@ -232,8 +363,8 @@ mod getters_tests {
     #[test]
     fn test_get_bool() {
         let bytearray = [0b10101010];
-        assert!(get_bool(&bytearray, 0, 1));
-        assert!(!get_bool(&bytearray, 0, 0));
+        assert!(get_bool(&bytearray, 0, 1).unwrap());
+        assert!(!get_bool(&bytearray, 0, 0).unwrap());
     }
 
     #[test]
@@ -248,6 +379,18 @@ mod getters_tests {
         assert_eq!(get_word(&bytearray, 0), 0x1234);
     }
 
+    #[test]
+    fn test_get_word_bits() {
+        let bits = get_word_bits(&[0x80, 0x01, 0x12, 0x34], 2);
+        let expected: Vec<usize> = (0..16).filter(|bit| 0x1234 & (1 << bit) != 0).collect();
+        let set: Vec<usize> = (0..16).filter(|&bit| bits[bit]).collect();
+        assert_eq!(set, expected);
+
+        let bits = get_word_bits(&[0x80, 0x01], 0);
+        assert!(bits[0] && bits[15]);
+        assert_eq!(bits.iter().filter(|bit| **bit).count(), 2);
+    }
+
     #[test]
     fn test_get_int() {
         let bytearray = [0xFF, 0xD6];
@@ -272,10 +415,37 @@ mod getters_tests {
         assert_eq!(get_fstring(bytearray, 0, 5, true), "hello");
     }
 
+    #[test]
+    fn test_get_fstring_non_utf8() {
+        let bytearray = [b'M', 0xFC, b'h', b'l', b'e', b' ', b' '];
+        assert_eq!(get_fstring(&bytearray, 0, 7, true), "M\u{FFFD}hle");
+        assert_eq!(get_fstring(&bytearray, 0, 7, false), "M\u{FFFD}hle  ");
+    }
+
+    #[test]
+    fn test_get_char_array() {
+        let bytearray = [b'x', b'M', b'1', b' ', 0xB0, b'C'];
+        assert_eq!(get_char_array(&bytearray, 1, 3).unwrap(), "M1 ");
+        assert!(get_char_array(&bytearray, 4, 2).is_err());
+        assert!(get_char_array(&bytearray, 5, 2).is_err());
+    }
+
     #[test]
     fn test_get_string() {
         let bytearray = [5, 4, b'h', b'e', b'l', b'l', b'o'];
-        assert_eq!(get_string(&bytearray, 0), "hell");
+        assert_eq!(get_string(&bytearray, 0).unwrap(), "hell");
+    }
+
+    #[test]
+    fn test_get_string_bytes() {
+        let bytearray = [5, 4, b'h', b'e', b'l', b'l', b'o'];
+        assert_eq!(
+            get_string_bytes(&bytearray, 0),
+            get_string(&bytearray, 0).unwrap().as_bytes()
+        );
+        assert_eq!(get_string_bytes(&[2, 3, b'a', b'b', b'c'], 0), b"");
+        assert_eq!(get_string_bytes(&[5, 4, b'a'], 0), b"");
+        assert_eq!(get_string_bytes(&[5], 0), b"");
     }
 
     #[test]
@@ -302,17 +472,123 @@ mod getters_tests {
         assert_eq!(get_s5time(&bytearray, 0), "23.4s");
     }
 
+    #[test]
+    fn test_get_bool_array() {
+        let bytearray = [0b1000_0000, 0b0000_0101];
+        assert_eq!(
+            get_bool_array(&bytearray, 0, 6, 5).unwrap(),
+            vec![false, true, true, false, true]
+        );
+        assert_eq!(get_bool_array(&bytearray, 1, 0, 0).unwrap(), vec![]);
+        assert!(get_bool_array(&bytearray, 1, 6, 3).is_err());
+        assert!(get_bool_array(&bytearray, 0, 8, 1).is_err());
+    }
+
+    #[test]
+    fn test_get_real_auto() {
+        let big = 123.25f32.to_be_bytes();
+        assert_eq!(
+            get_real_auto(&big, 0).unwrap(),
+            (123.25, ByteOrder::BigEndian)
+        );
+
+        let little = 100.0f32.to_le_bytes();
+        assert_eq!(
+            get_real_auto(&little, 0).unwrap(),
+            (100.0, ByteOrder::LittleEndian)
+        );
+
+        let zero = [0u8; 4];
+        assert_eq!(
+            get_real_auto(&zero, 0).unwrap(),
+            (0.0, ByteOrder::BigEndian)
+        );
+
+        assert!(get_real_auto(&zero, 1).is_err());
+    }
+
+    #[test]
+    fn test_get_s5time_duration() {
+        let bytearray = [0x21, 0x50];
+        assert_eq!(
+            get_s5time_duration(&bytearray, 0).unwrap(),
+            Duration::from_secs(150)
+        );
+        assert!(get_s5time_duration(&[0x40, 0x00], 0).is_err());
+        assert!(get_s5time_duration(&[0x00, 0x0A], 0).is_err());
+    }
+
+    #[test]
+    fn test_get_counter() {
+        let bytearray = [0x09, 0x99];
+        assert_eq!(get_counter(&bytearray, 0).unwrap(), 999);
+        assert!(get_counter(&[0x00, 0xAB], 0).is_err());
+    }
+
     #[test]
     fn test_get_dt() {
         let bytearray = [0x24, 0x12, 0x12, 0x12, 0x30, 0x30, 0x30, 0x00];
         assert_eq!(get_dt(&bytearray, 0), "2024-12-12 12:30:30.300 UTC");
     }
 
+    #[test]
+    fn test_get_dt_array() {
+        let bytearray = [
+            0x00, 0x24, 0x12, 0x12, 0x12, 0x30, 0x30, 0x30, 0x00, 0x99, 0x01, 0x31, 0x23, 0x59,
+            0x59, 0x99, 0x90,
+        ];
+        let values = get_dt_array(&bytearray, 1, 2).unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[0].to_string(), "2024-12-12 12:30:30.300 UTC");
+        assert_eq!(values[1].to_string(), "1999-01-31 23:59:59.999 UTC");
+        assert!(get_dt_array(&bytearray, 1, 3).is_err());
+        assert!(get_dt_array(&bytearray, 0, 0).unwrap().is_empty());
+        // 月份为 0 的条目返回错误而不是 panic
+        assert!(get_dt_array(&[0x24, 0x00, 0x01, 0, 0, 0, 0, 0], 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_try_get_date_time_object() {
+        let bytearray = [0x24, 0x12, 0x12, 0x12, 0x30, 0x30, 0x30, 0x00];
+        assert_eq!(
+            try_get_date_time_object(&bytearray, 0).unwrap(),
+            get_date_time_object(&bytearray, 0)
+        );
+        assert!(try_get_date_time_object(&bytearray, 1).is_err());
+        assert!(try_get_date_time_object(&[0x24, 0x02, 0x30, 0, 0, 0, 0, 0], 0).is_err());
+        assert!(try_get_date_time_object(&[0x24, 0x01, 0x01, 0x25, 0, 0, 0, 0], 0).is_err());
+    }
+
     #[test]
 
     fn test_get_time() {
         let bytearray = [0x7f, 0xff, 0xff, 0xff];
         assert_eq!(get_time(&bytearray, 0), "24:20:31:23.647");
+        assert_eq!(get_time(&[0, 0, 0, 0], 0), "0:0:0:0.0");
+        // -1 天 2 小时 3 分 4 秒 5 毫秒
+        let bytearray = (-93_784_005i32).to_be_bytes();
+        assert_eq!(get_time(&bytearray, 0), "-1:2:3:4.5");
+        let bytearray = i32::MIN.to_be_bytes();
+        assert_eq!(get_time(&bytearray, 0), "-24:20:31:23.648");
+    }
+
+    #[test]
+    fn test_get_time_duration() {
+        let bytearray = [0x7f, 0xff, 0xff, 0xff];
+        assert_eq!(
+            get_time_duration(&bytearray, 0).unwrap(),
+            chrono::Duration::milliseconds(i32::MAX as i64)
+        );
+        assert_eq!(
+            get_time_duration(&[0, 0, 0, 0], 0).unwrap(),
+            chrono::Duration::zero()
+        );
+        let bytearray = i32::MIN.to_be_bytes();
+        assert_eq!(
+            get_time_duration(&bytearray, 0).unwrap(),
+            chrono::Duration::milliseconds(i32::MIN as i64)
+        );
+        assert!(get_time_duration(&bytearray, 1).is_err());
     }
 
     #[test]
@@ -0,0 +1,66 @@
+use std::fmt::Write;
+
+/// 以 xxd 的格式输出十六进制与 ASCII 对照，偏移从 0 开始
+///
+/// # Examples
+/// ```
+/// use rust_snap7::utils::hexdump::hexdump;
+///
+/// assert_eq!(hexdump(b"S7\x00\x01"), "00000000: 5337 0001                                S7..\n");
+/// ```
+pub fn hexdump(data: &[u8]) -> String {
+    hexdump_at(data, 0)
+}
+
+/// 以 xxd 的格式输出十六进制与 ASCII 对照，偏移从 base_offset 开始
+pub fn hexdump_at(data: &[u8], base_offset: usize) -> String {
+    let mut out = String::new();
+    for (line, chunk) in data.chunks(16).enumerate() {
+        let mut hex = String::with_capacity(39);
+        for (i, byte) in chunk.iter().enumerate() {
+            if i > 0 && i % 2 == 0 {
+                hex.push(' ');
+            }
+            write!(hex, "{:02x}", byte).unwrap();
+        }
+        let ascii: String = chunk
+            .iter()
+            .map(|&b| {
+                if b.is_ascii_graphic() || b == b' ' {
+                    b as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(
+            out,
+            "{:08x}: {:<39}  {}",
+            base_offset + line * 16,
+            hex,
+            ascii
+        )
+        .unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump() {
+        let data: Vec<u8> = (0x30..0x30 + 20).collect();
+        assert_eq!(
+            hexdump(&data),
+            "00000000: 3031 3233 3435 3637 3839 3a3b 3c3d 3e3f  0123456789:;<=>?\n\
+             00000010: 4041 4243                                @ABC\n"
+        );
+        assert_eq!(
+            hexdump_at(&[0x00, 0x7f, b'a'], 0x20),
+            "00000020: 007f 61                                  ..a\n"
+        );
+        assert_eq!(hexdump(&[]), "");
+    }
+}
@@ -0,0 +1,261 @@
+use super::getters;
+use crate::client::S7Client;
+use std::collections::HashMap;
+
+/// S7 数据类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum S7Type {
+    /// BOOL，参数为位索引(0..7)
+    Bool(usize),
+    Byte,
+    Word,
+    Int,
+    DWord,
+    DInt,
+    Real,
+    LReal,
+    /// STRING，参数为最大字符数
+    String(usize),
+}
+
+impl S7Type {
+    /// 该类型在 DB 中占用的字节数
+    pub fn size(&self) -> usize {
+        match self {
+            S7Type::Bool(_) | S7Type::Byte => 1,
+            S7Type::Word | S7Type::Int => 2,
+            S7Type::DWord | S7Type::DInt | S7Type::Real => 4,
+            S7Type::LReal => 8,
+            S7Type::String(max_length) => max_length + 2,
+        }
+    }
+
+    /// 从缓冲区的字节偏移处解码该类型的值
+    pub fn decode(&self, bytearray: &[u8], offset: usize) -> Result<Value, String> {
+        if bytearray.len() < offset + self.size() {
+            return Err("Buffer has no enough data to decoding".to_string());
+        }
+        Ok(match *self {
+            S7Type::Bool(bit) => Value::Bool(getters::get_bool(bytearray, offset, bit)?),
+            S7Type::Byte => Value::Byte(getters::get_byte(bytearray, offset)),
+            S7Type::Word => Value::Word(getters::get_word(bytearray, offset)),
+            S7Type::Int => Value::Int(getters::get_int(bytearray, offset)),
+            S7Type::DWord => Value::DWord(getters::get_dword(bytearray, offset)),
+            S7Type::DInt => Value::DInt(getters::get_dint(bytearray, offset)),
+            S7Type::Real => Value::Real(getters::get_real(bytearray, offset)),
+            S7Type::LReal => Value::LReal(getters::get_lreal(bytearray, offset)),
+            S7Type::String(_) => Value::String(getters::get_string(bytearray, offset)?),
+        })
+    }
+}
+
+/// 解码后的值
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Byte(u8),
+    Word(u16),
+    Int(i16),
+    DWord(u32),
+    DInt(i32),
+    Real(f32),
+    LReal(f64),
+    String(String),
+}
+
+/// 运行时 DB 布局描述
+///
+/// # Examples
+/// ```
+/// use rust_snap7::utils::layout::{Layout, S7Type};
+///
+/// let layout = Layout::new([
+///     ("speed", S7Type::Real, 0),
+///     ("running", S7Type::Bool(0), 4),
+/// ]);
+/// assert_eq!(layout.size(), 5);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Layout {
+    fields: Vec<(String, S7Type, usize)>,
+}
+
+impl Layout {
+    /// 由 `(名称, 类型, 字节偏移)` 列表创建布局
+    pub fn new<I, S>(fields: I) -> Self
+    where
+        I: IntoIterator<Item = (S, S7Type, usize)>,
+        S: Into<String>,
+    {
+        Layout {
+            fields: fields
+                .into_iter()
+                .map(|(name, s7_type, offset)| (name.into(), s7_type, offset))
+                .collect(),
+        }
+    }
+
+    /// 覆盖所有字段所需的字节数(从偏移 0 开始)
+    pub fn size(&self) -> usize {
+        self.fields
+            .iter()
+            .map(|(_, s7_type, offset)| offset + s7_type.size())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// 按布局从缓冲区解码所有字段
+    pub fn decode(&self, bytearray: &[u8]) -> Result<HashMap<String, Value>, String> {
+        if bytearray.len() < self.size() {
+            return Err("Buffer has no enough data to decoding".to_string());
+        }
+        let mut record = HashMap::with_capacity(self.fields.len());
+        for (name, s7_type, offset) in &self.fields {
+            record.insert(name.clone(), s7_type.decode(bytearray, *offset)?);
+        }
+        Ok(record)
+    }
+}
+
+/// 按相同布局连续排列的记录数组(如配方: 20 × {REAL, REAL, INT})
+///
+/// # Examples
+/// ```
+/// use rust_snap7::utils::layout::{Layout, RecordArray, S7Type};
+///
+/// let layout = Layout::new([("speed", S7Type::Real, 0), ("count", S7Type::Int, 4)]);
+/// let recipe = RecordArray::new(layout, 20, 6);
+/// assert_eq!(recipe.size(), 120);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RecordArray {
+    layout: Layout,
+    count: usize,
+    stride: usize,
+}
+
+impl RecordArray {
+    /// 由单条记录的布局、记录数量和相邻记录的字节间距创建数组
+    pub fn new(layout: Layout, count: usize, stride: usize) -> Self {
+        RecordArray {
+            layout,
+            count,
+            stride,
+        }
+    }
+
+    /// 覆盖所有记录所需的字节数(从偏移 0 开始)
+    pub fn size(&self) -> usize {
+        match self.count {
+            0 => 0,
+            count => self.stride * (count - 1) + self.layout.size(),
+        }
+    }
+
+    /// 从缓冲区解码所有记录
+    pub fn decode(&self, bytearray: &[u8]) -> Result<Vec<HashMap<String, Value>>, String> {
+        if bytearray.len() < self.size() {
+            return Err("Buffer has no enough data to decoding".to_string());
+        }
+        (0..self.count)
+            .map(|i| self.layout.decode(&bytearray[i * self.stride..]))
+            .collect()
+    }
+
+    ///
+    /// 从 DB 中读取并解码所有记录，读取前检查数组是否超出 DB 的大小。
+    ///
+    /// **输入参数:**
+    ///
+    ///  - client: 已连接的客户端
+    ///  - db_number: 数据块(DB)编号
+    ///  - start: 第一条记录的起始字节地址
+    ///
+    /// **返回值:**
+    ///
+    ///  - Ok: 按顺序排列的记录
+    ///  - Err: 超出 DB 大小、读取或解码失败
+    ///
+    pub fn read_all(
+        &self,
+        client: &S7Client,
+        db_number: i32,
+        start: i32,
+    ) -> anyhow::Result<Vec<HashMap<String, Value>>> {
+        let db_size = client.db_size(db_number)?;
+        let end = start.max(0) as usize + self.size();
+        if start < 0 || end > db_size {
+            anyhow::bail!(
+                "record array DB{}.{}..{} exceeds the DB size {}",
+                db_number,
+                start,
+                end,
+                db_size
+            );
+        }
+        let mut buff = vec![0u8; self.size()];
+        if !buff.is_empty() {
+            client.db_read(db_number, start, buff.len() as i32, &mut buff)?;
+        }
+        self.decode(&buff).map_err(anyhow::Error::msg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let layout = Layout::new([
+            ("temperature", S7Type::Real, 0),
+            ("count", S7Type::Int, 4),
+            ("alarm", S7Type::Bool(2), 6),
+            ("name", S7Type::String(8), 8),
+        ]);
+        assert_eq!(layout.size(), 18);
+
+        let mut buff = [0u8; 18];
+        buff[0..4].copy_from_slice(&21.5f32.to_be_bytes());
+        buff[4..6].copy_from_slice(&(-7i16).to_be_bytes());
+        buff[6] = 0b0000_0100;
+        buff[8..13].copy_from_slice(&[8, 3, b'a', b'b', b'c']);
+
+        let record = layout.decode(&buff).unwrap();
+        assert_eq!(record["temperature"], Value::Real(21.5));
+        assert_eq!(record["count"], Value::Int(-7));
+        assert_eq!(record["alarm"], Value::Bool(true));
+        assert_eq!(record["name"], Value::String("abc".to_string()));
+
+        assert!(layout.decode(&buff[..10]).is_err());
+    }
+
+    #[test]
+    fn test_record_array_read_all() {
+        use crate::{model::*, test_support::*};
+
+        let mut db_buff = [0u8; 20];
+        for (i, (setpoint, count)) in [(1.5f32, 3i16), (-2.0, 7)].into_iter().enumerate() {
+            let offset = 2 + i * 8;
+            db_buff[offset..offset + 4].copy_from_slice(&setpoint.to_be_bytes());
+            db_buff[offset + 4..offset + 6].copy_from_slice(&count.to_be_bytes());
+        }
+        let (server, port) = start_server(&mut [(AreaCode::S7AreaDB, 3, &mut db_buff)]);
+        let client = connect_client(port);
+
+        let layout = Layout::new([("setpoint", S7Type::Real, 0), ("count", S7Type::Int, 4)]);
+        let recipe = RecordArray::new(layout, 2, 8);
+        assert_eq!(recipe.size(), 14);
+        let records = recipe.read_all(&client, 3, 2).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0]["setpoint"], Value::Real(1.5));
+        assert_eq!(records[0]["count"], Value::Int(3));
+        assert_eq!(records[1]["setpoint"], Value::Real(-2.0));
+        assert_eq!(records[1]["count"], Value::Int(7));
+
+        assert!(recipe.read_all(&client, 3, 8).is_err());
+
+        client.disconnect().unwrap();
+        server.stop().unwrap();
+    }
+}
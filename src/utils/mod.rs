@@ -1,2 +1,6 @@
+pub mod address;
+pub mod buffer;
 pub mod getters;
+pub mod hexdump;
+pub mod layout;
 pub mod setters;
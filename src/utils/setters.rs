@@ -20,6 +20,25 @@ pub fn set_bool(
     Ok(())
 }
 
+pub fn set_bool_array(
+    bytearray: &mut [u8],
+    byte_index: usize,
+    bool_index: usize,
+    values: &[bool],
+) -> Result<(), String> {
+    if bool_index > 7 {
+        return Err(format!("bool_index {} out of range", bool_index));
+    }
+    let first_bit = byte_index * 8 + bool_index;
+    if bytearray.len() * 8 < first_bit + values.len() {
+        return Err("Buffer has no enough space to encoding".to_string());
+    }
+    for (bit, value) in (first_bit..).zip(values) {
+        set_bool(bytearray, bit / 8, bit % 8, *value)?;
+    }
+    Ok(())
+}
+
 pub fn set_byte(bytearray: &mut [u8], byte_index: usize, value: u8) {
     bytearray[byte_index] = value;
 }
@@ -28,6 +47,15 @@ pub fn set_word(bytearray: &mut [u8], byte_index: usize, value: u16) {
     bytearray[byte_index..byte_index + 2].copy_from_slice(&value.to_be_bytes());
 }
 
+/// 将 16 个位编码为大端 WORD，下标 i 对应数值的第 i 位(0 为最低位)，与 `get_word_bits` 相反
+pub fn set_word_bits(bytearray: &mut [u8], byte_index: usize, bits: [bool; 16]) {
+    let word = bits
+        .iter()
+        .enumerate()
+        .fold(0u16, |word, (bit, value)| word | ((*value as u16) << bit));
+    set_word(bytearray, byte_index, word);
+}
+
 pub fn set_int(bytearray: &mut [u8], byte_index: usize, value: i16) {
     bytearray[byte_index..byte_index + 2].copy_from_slice(&value.to_be_bytes());
 }
@@ -89,6 +117,18 @@ pub fn set_lreal(bytearray: &mut [u8], byte_index: usize, value: f64) {
     bytearray[byte_index..byte_index + 8].copy_from_slice(&value.to_be_bytes());
 }
 
+pub fn set_lint(bytearray: &mut [u8], byte_index: usize, value: i64) {
+    bytearray[byte_index..byte_index + 8].copy_from_slice(&value.to_be_bytes());
+}
+
+pub fn set_lword(bytearray: &mut [u8], byte_index: usize, value: u64) {
+    bytearray[byte_index..byte_index + 8].copy_from_slice(&value.to_be_bytes());
+}
+
+pub fn set_ulint(bytearray: &mut [u8], byte_index: usize, value: u64) {
+    bytearray[byte_index..byte_index + 8].copy_from_slice(&value.to_be_bytes());
+}
+
 pub fn set_char(bytearray: &mut [u8], byte_index: usize, value: char) -> Result<(), String> {
     if value.is_ascii() {
         bytearray[byte_index] = value as u8;
@@ -98,6 +138,156 @@ pub fn set_char(bytearray: &mut [u8], byte_index: usize, value: char) -> Result<
     }
 }
 
+pub fn set_char_array(
+    bytearray: &mut [u8],
+    byte_index: usize,
+    value: &str,
+    count: usize,
+) -> Result<(), String> {
+    if !value.is_ascii() {
+        return Err(format!("Non-ASCII character in: {}", value));
+    }
+    if value.len() > count {
+        return Err(format!(
+            "String length {} exceeds the array length {}",
+            value.len(),
+            count
+        ));
+    }
+    if bytearray.len() < byte_index + count {
+        return Err("Buffer has no enough space to encoding".to_string());
+    }
+    let data = &mut bytearray[byte_index..byte_index + count];
+    data[..value.len()].copy_from_slice(value.as_bytes());
+    data[value.len()..].fill(b' ');
+    Ok(())
+}
+
+/// 写入 S7 STRING: 最大长度字节、实际长度字节和 ASCII 字符，未使用的尾部字节清零
+pub fn set_string(
+    bytearray: &mut [u8],
+    byte_index: usize,
+    value: &str,
+    max_length: usize,
+) -> Result<(), String> {
+    if max_length > 254 {
+        return Err(format!("String max length {} exceeds 254", max_length));
+    }
+    if !value.is_ascii() {
+        return Err(format!("Non-ASCII character in: {}", value));
+    }
+    if value.len() > max_length {
+        return Err(format!(
+            "String length {} exceeds the max length {}",
+            value.len(),
+            max_length
+        ));
+    }
+    if bytearray.len() < byte_index + max_length + 2 {
+        return Err("Buffer has no enough space to encoding".to_string());
+    }
+    let data = &mut bytearray[byte_index..byte_index + max_length + 2];
+    data[0] = max_length as u8;
+    data[1] = value.len() as u8;
+    data[2..2 + value.len()].copy_from_slice(value.as_bytes());
+    data[2 + value.len()..].fill(0);
+    Ok(())
+}
+
+/// 写入定长字符串，pad 为 true 时用空格填充至 max_length，否则保留剩余字节不变
+pub fn set_fstring(
+    bytearray: &mut [u8],
+    byte_index: usize,
+    value: &str,
+    max_length: usize,
+    pad: bool,
+) -> Result<(), String> {
+    if !value.is_ascii() {
+        return Err(format!("Non-ASCII character in: {}", value));
+    }
+    if value.len() > max_length {
+        return Err(format!(
+            "String length {} exceeds the max length {}",
+            value.len(),
+            max_length
+        ));
+    }
+    if bytearray.len() < byte_index + max_length {
+        return Err("Buffer has no enough space to encoding".to_string());
+    }
+    let data = &mut bytearray[byte_index..byte_index + max_length];
+    data[..value.len()].copy_from_slice(value.as_bytes());
+    if pad {
+        data[value.len()..].fill(b' ');
+    }
+    Ok(())
+}
+
+/// 写入 S5TIME，选用能以 999 个计数表示该时长的最小时基，低于时基精度的部分被舍去
+pub fn set_s5time(bytearray: &mut [u8], byte_index: usize, value: Duration) -> Result<(), String> {
+    let millis = value.as_millis();
+    let Some((base, time_base_ms)) = [10u128, 100, 1000, 10000]
+        .into_iter()
+        .enumerate()
+        .find(|(_, time_base_ms)| millis / time_base_ms <= 999)
+    else {
+        return Err(format!("S5TIME out of range: {:?}", value));
+    };
+    if bytearray.len() < byte_index + 2 {
+        return Err("Buffer has no enough space to encoding".to_string());
+    }
+    let count = (millis / time_base_ms) as u16;
+    let bcd = ((count / 100) << 8) | ((count / 10 % 10) << 4) | (count % 10);
+    let encoded = ((base as u16) << 12) | bcd;
+    bytearray[byte_index..byte_index + 2].copy_from_slice(&encoded.to_be_bytes());
+    Ok(())
+}
+
+/// 写入 TIME_OF_DAY(自午夜起的毫秒数，4 字节)
+pub fn set_tod(bytearray: &mut [u8], byte_index: usize, value: Duration) -> Result<(), String> {
+    if value.as_secs() >= 86400 {
+        return Err(format!("Time of day out of range: {:?}", value));
+    }
+    if bytearray.len() < byte_index + 4 {
+        return Err("Buffer has no enough space to encoding".to_string());
+    }
+    let millis = value.as_millis() as u32;
+    bytearray[byte_index..byte_index + 4].copy_from_slice(&millis.to_be_bytes());
+    Ok(())
+}
+
+/// 写入 DATE_AND_TIME(8 字节 BCD)，年份范围 1990..=2089，精度为毫秒
+pub fn set_dt(
+    bytearray: &mut [u8],
+    byte_index: usize,
+    value: chrono::DateTime<chrono::Utc>,
+) -> Result<(), String> {
+    use chrono::{Datelike, Timelike};
+
+    fn byte_to_bcd(value: u32) -> u8 {
+        (((value / 10) << 4) | (value % 10)) as u8
+    }
+    if !(1990..=2089).contains(&value.year()) {
+        return Err(format!("Date out of range: {}", value));
+    }
+    if bytearray.len() < byte_index + 8 {
+        return Err("Buffer has no enough space to encoding".to_string());
+    }
+    // 闰秒时 chrono 的纳秒部分超过 1 秒，按 999 毫秒处理
+    let millis = (value.nanosecond() / 1_000_000).min(999);
+    let data = &mut bytearray[byte_index..byte_index + 8];
+    data[0] = byte_to_bcd(value.year() as u32 % 100);
+    data[1] = byte_to_bcd(value.month());
+    data[2] = byte_to_bcd(value.day());
+    data[3] = byte_to_bcd(value.hour());
+    data[4] = byte_to_bcd(value.minute());
+    data[5] = byte_to_bcd(value.second());
+    data[6] = byte_to_bcd(millis / 10);
+    // 低 4 位为星期(1 = 星期日)
+    data[7] = ((millis % 10) << 4) as u8 | (value.weekday().number_from_sunday() as u8);
+    Ok(())
+}
+
 pub fn set_date(
     bytearray: &mut [u8],
     byte_index: usize,
@@ -128,6 +318,15 @@ mod setters_tests {
         assert_eq!(data, vec![0]);
     }
 
+    #[test]
+    fn test_set_bool_array() {
+        let mut data = vec![0xFF, 0x00];
+        set_bool_array(&mut data, 0, 6, &[false, true, true, false, true]).unwrap();
+        assert_eq!(data, vec![0b1011_1111, 0b0000_0101]);
+        assert!(set_bool_array(&mut data, 1, 7, &[true, true]).is_err());
+        assert_eq!(data, vec![0b1011_1111, 0b0000_0101]);
+    }
+
     #[test]
     fn test_set_byte() {
         let mut data = vec![0; 1];
@@ -142,6 +341,20 @@ mod setters_tests {
         assert_eq!(data, vec![255, 255]);
     }
 
+    #[test]
+    fn test_set_word_bits() {
+        use crate::utils::getters::get_word_bits;
+
+        let mut data = [0u8; 3];
+        let mut bits = [false; 16];
+        bits[0] = true;
+        bits[3] = true;
+        bits[15] = true;
+        set_word_bits(&mut data, 1, bits);
+        assert_eq!(data, [0x00, 0x80, 0x09]);
+        assert_eq!(get_word_bits(&data, 1), bits);
+    }
+
     #[test]
     fn test_set_int() {
         let mut data = vec![0; 2];
@@ -226,6 +439,91 @@ mod setters_tests {
         assert_eq!(bytearray[2..10], 12.34f64.to_be_bytes());
     }
 
+    #[test]
+    fn test_set_lint_lword_ulint() {
+        use crate::utils::getters::{get_lint, get_lword, get_ulint};
+
+        let mut bytearray = [0u8; 26];
+        set_lint(&mut bytearray, 0, -9_000_000_000_000);
+        set_lword(&mut bytearray, 8, 0x0123_4567_89AB_CDEF);
+        set_ulint(&mut bytearray, 16, u64::MAX - 1);
+        assert_eq!(get_lint(&bytearray, 0), -9_000_000_000_000);
+        assert_eq!(get_lword(&bytearray, 8), 0x0123_4567_89AB_CDEF);
+        assert_eq!(get_ulint(&bytearray, 16), u64::MAX - 1);
+        assert_eq!(
+            bytearray[8..16],
+            [0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF]
+        );
+    }
+
+    #[test]
+    fn test_set_s5time() {
+        use crate::utils::getters::{get_s5time, get_s5time_duration};
+
+        let mut bytearray = [0u8; 2];
+        set_s5time(&mut bytearray, 0, Duration::from_millis(23_400)).unwrap();
+        assert_eq!(bytearray, [0x12, 0x34]);
+        assert_eq!(get_s5time(&bytearray, 0), "23.4s");
+
+        for millis in [0, 10, 9_990, 10_000, 99_900, 100_000, 999_000, 9_990_000] {
+            let value = Duration::from_millis(millis);
+            set_s5time(&mut bytearray, 0, value).unwrap();
+            assert_eq!(get_s5time_duration(&bytearray, 0).unwrap(), value);
+        }
+        // 超出时基精度的部分被舍去
+        set_s5time(&mut bytearray, 0, Duration::from_millis(12_345)).unwrap();
+        assert_eq!(
+            get_s5time_duration(&bytearray, 0).unwrap(),
+            Duration::from_millis(12_300)
+        );
+
+        assert!(set_s5time(&mut bytearray, 0, Duration::from_secs(10_000)).is_err());
+        assert!(set_s5time(&mut bytearray, 1, Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_set_tod() {
+        let mut bytearray = [0u8; 6];
+        let value = Duration::from_millis(13 * 3_600_000 + 45 * 60_000 + 30_250);
+        set_tod(&mut bytearray, 2, value).unwrap();
+        assert_eq!(crate::utils::getters::get_tod(&bytearray, 2), value);
+
+        assert!(set_tod(&mut bytearray, 0, Duration::from_secs(86400)).is_err());
+        assert!(set_tod(&mut bytearray, 4, value).is_err());
+    }
+
+    #[test]
+    fn test_set_dt() {
+        use chrono::{NaiveDate, TimeZone, Utc};
+
+        let mut bytearray = [0u8; 10];
+        let value = NaiveDate::from_ymd_opt(2024, 2, 29)
+            .unwrap()
+            .and_hms_milli_opt(13, 45, 30, 123)
+            .unwrap()
+            .and_utc();
+        set_dt(&mut bytearray, 1, value).unwrap();
+        assert_eq!(
+            bytearray[1..9],
+            [0x24, 0x02, 0x29, 0x13, 0x45, 0x30, 0x12, 0x35]
+        );
+        assert_eq!(
+            crate::utils::getters::get_date_time_object(&bytearray, 1),
+            value
+        );
+
+        let value = Utc.with_ymd_and_hms(1995, 12, 31, 23, 59, 59).unwrap();
+        set_dt(&mut bytearray, 0, value).unwrap();
+        assert_eq!(
+            crate::utils::getters::get_date_time_object(&bytearray, 0),
+            value
+        );
+
+        let too_late = Utc.with_ymd_and_hms(2090, 1, 1, 0, 0, 0).unwrap();
+        assert!(set_dt(&mut bytearray, 0, too_late).is_err());
+        assert!(set_dt(&mut bytearray, 4, value).is_err());
+    }
+
     #[test]
     fn test_set_char() {
         let mut bytearray = [0u8; 10];
@@ -233,6 +531,20 @@ mod setters_tests {
         assert_eq!(bytearray[2], b'A');
     }
 
+    #[test]
+    fn test_set_char_get_char() {
+        use crate::utils::getters::get_char;
+
+        let mut bytearray = [0u8; 2];
+        for value in ['A', 'z', '0', ' ', '\x7F'] {
+            set_char(&mut bytearray, 1, value).unwrap();
+            assert_eq!(get_char(&bytearray, 1).unwrap(), value);
+        }
+        bytearray[0] = 0x80;
+        assert!(get_char(&bytearray, 0).is_err());
+        assert!(get_char(&bytearray, 2).is_err());
+    }
+
     #[test]
     fn test_set_char_non_ascii() {
         let mut bytearray = [0u8; 10];
@@ -240,6 +552,59 @@ mod setters_tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_set_char_array() {
+        let mut bytearray = [0u8; 8];
+        set_char_array(&mut bytearray, 1, "PUMP", 6).unwrap();
+        assert_eq!(&bytearray, b"\0PUMP  \0");
+        assert_eq!(
+            crate::utils::getters::get_char_array(&bytearray, 1, 6).unwrap(),
+            "PUMP  "
+        );
+
+        assert!(set_char_array(&mut bytearray, 0, "Grüße", 8).is_err());
+        assert!(set_char_array(&mut bytearray, 0, "TOO LONG", 4).is_err());
+        assert!(set_char_array(&mut bytearray, 4, "ABC", 6).is_err());
+    }
+
+    #[test]
+    fn test_set_string() {
+        let mut bytearray = [0xFFu8; 10];
+        set_string(&mut bytearray, 1, "PUMP", 6).unwrap();
+        assert_eq!(&bytearray, b"\xFF\x06\x04PUMP\0\0\xFF");
+        assert_eq!(
+            crate::utils::getters::get_string(&bytearray, 1).unwrap(),
+            "PUMP"
+        );
+        set_string(&mut bytearray, 1, "", 6).unwrap();
+        assert_eq!(
+            crate::utils::getters::get_string(&bytearray, 1).unwrap(),
+            ""
+        );
+
+        assert!(set_string(&mut bytearray, 0, "TOO LONG", 4).is_err());
+        assert!(set_string(&mut bytearray, 0, "Grüße", 8).is_err());
+        assert!(set_string(&mut bytearray, 0, "A", 255).is_err());
+        assert!(set_string(&mut bytearray, 4, "ABC", 6).is_err());
+    }
+
+    #[test]
+    fn test_set_fstring() {
+        let mut bytearray = [b'x'; 8];
+        set_fstring(&mut bytearray, 1, "PUMP", 6, true).unwrap();
+        assert_eq!(&bytearray, b"xPUMP  x");
+        assert_eq!(
+            crate::utils::getters::get_fstring(&bytearray, 1, 6, true),
+            "PUMP"
+        );
+        set_fstring(&mut bytearray, 1, "AB", 6, false).unwrap();
+        assert_eq!(&bytearray, b"xABMP  x");
+
+        assert!(set_fstring(&mut bytearray, 0, "Grüße", 8, true).is_err());
+        assert!(set_fstring(&mut bytearray, 0, "TOO LONG", 4, true).is_err());
+        assert!(set_fstring(&mut bytearray, 4, "ABC", 6, true).is_err());
+    }
+
     #[test]
     fn test_parse_time_string_valid() {
         let duration = parse_time_string("0:0:0:1:0.0").unwrap();
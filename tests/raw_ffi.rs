@@ -0,0 +1,22 @@
+#![cfg(feature = "raw-ffi")]
+
+use rust_snap7::ffi::*;
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int};
+
+#[test]
+fn test_raw_ffi() {
+    unsafe {
+        let mut client = Cli_Create();
+        assert_ne!(client, 0);
+
+        let mut text = [0 as c_char; 1024];
+        let res = Cli_ErrorText(errCliInvalidParams as c_int, text.as_mut_ptr(), 1024);
+        assert_eq!(res, 0);
+        let text = CStr::from_ptr(text.as_ptr()).to_string_lossy();
+        assert!(!text.is_empty());
+
+        Cli_Destroy(&mut client);
+        assert_eq!(client, 0);
+    }
+}